@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use crate::event::PhysicsEvent;
+use crate::physics_object::{get_pair_mut, ObjectHandle, PhysicsWorld};
+
+impl PhysicsWorld {
+
+    /// Merges each pair into whichever of the two is heavier, conserving mass, momentum, angular momentum, charge and
+    /// heat and keeping the combined area, then removes the absorbed objects. Kinematic objects and members of bodies,
+    /// balloons and joints never merge, since those would lose track of them.
+    pub(crate) fn merge_objects(&mut self, pairs: &[(ObjectHandle, ObjectHandle)]) {
+
+        let structure_members = self.get_structure_members();
+        let mut absorbed: HashSet<ObjectHandle> = HashSet::new();
+
+        for &(a, b) in pairs {
+
+            if absorbed.contains(&a) || absorbed.contains(&b) {
+                continue;
+            }
+
+            if structure_members.contains(&a) || structure_members.contains(&b) {
+                continue;
+            }
+
+            let (index_a, index_b) = match (self.get_index(a), self.get_index(b)) {
+                (Some(index_a), Some(index_b)) if index_a != index_b => (index_a, index_b),
+                _ => continue,
+            };
+
+            if self.get_objects()[index_a].is_kinematic() || self.get_objects()[index_b].is_kinematic() {
+                continue;
+            }
+
+            let (survivor_index, absorbed_index) = if self.get_objects()[index_a].get_mass() >= self.get_objects()[index_b].get_mass() {
+                (index_a, index_b)
+            } else {
+                (index_b, index_a)
+            };
+
+            let (survivor, other) = get_pair_mut(self.get_objects_mut_no_wake(), survivor_index, absorbed_index);
+
+            let mass = survivor.get_mass() + other.get_mass();
+            let position = (survivor.get_position() * survivor.get_mass() + other.get_position() * other.get_mass()) / mass;
+            let velocity = (survivor.get_velocity() * survivor.get_mass() + other.get_velocity() * other.get_mass()) / mass;
+
+            // Spin about each centre, plus the orbit of each centre about the combined one.
+            let mut angular_momentum = 0.;
+            for part in [&*survivor, &*other] {
+                let arm = part.get_position() - position;
+                let momentum = part.get_velocity() * part.get_mass();
+                angular_momentum += part.get_inertia() * part.get_angular_velocity() + arm.x * momentum.y - arm.y * momentum.x;
+            }
+
+            let heat_capacity = survivor.get_mass() * survivor.get_material().get_heat_capacity();
+            let other_heat_capacity = other.get_mass() * other.get_material().get_heat_capacity();
+            if heat_capacity + other_heat_capacity > 0. {
+                survivor.set_temperature((survivor.get_temperature() * heat_capacity + other.get_temperature() * other_heat_capacity) / (heat_capacity + other_heat_capacity));
+            }
+
+            survivor.set_charge(survivor.get_charge() + other.get_charge());
+            survivor.set_mass(mass);
+            survivor.set_size((survivor.get_rest_size().powi(2) + other.get_rest_size().powi(2)).sqrt());
+            survivor.set_raw_position(position);
+            survivor.set_velocity(velocity);
+            survivor.set_angular_velocity(angular_momentum / survivor.get_inertia());
+            survivor.wake();
+
+            let survivor_handle = self.get_handles()[survivor_index];
+            let absorbed_handle = self.get_handles()[absorbed_index];
+            let survivor_size = self.get_objects()[survivor_index].get_size();
+
+            absorbed.insert(absorbed_handle);
+            self.grow_max_object_size(survivor_size);
+            self.push_event(PhysicsEvent::Merged {
+                survivor: survivor_handle,
+                absorbed: absorbed_handle,
+                position,
+            });
+
+        }
+
+        let absorbed: Vec<ObjectHandle> = absorbed.into_iter().collect();
+        self.remove_objects(&absorbed);
+
+    }
+
+}
@@ -0,0 +1,179 @@
+use std::f64::consts::PI;
+use cgmath::{InnerSpace, Vector2};
+use sfml::graphics::CircleShape;
+use crate::circle::Circle;
+use crate::constraint::DistanceConstraint;
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// Identifies a balloon added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type BalloonId = usize;
+
+/// The smallest fraction of its rest area a balloon's gas is taken to fill, so a crushed ring pushes back hard but
+/// never infinitely.
+const MIN_AREA_FRACTION: f64 = 0.01;
+
+/// The area enclosed by `positions` taken as a polygon, positive when they wind clockwise on screen.
+pub(crate) fn polygon_area(positions: &[Vector2<f64>]) -> f64 {
+
+    let mut area = 0.;
+
+    for index in 0..positions.len() {
+        let a = positions[index];
+        let b = positions[(index + 1) % positions.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+
+    return area / 2.;
+
+}
+
+/// A closed ring of objects holding in a gas.
+///
+/// The gas pushes every edge of the ring outwards with a pressure of `pressure * rest_area / area`, so squeezing the
+/// balloon below its rest area makes it push back harder. The ring itself is usually held together with
+/// [`DistanceConstraint`]s between neighbours, as [`PhysicsWorld::add_balloon_ring`] does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Balloon {
+
+    members: Vec<ObjectHandle>,
+    pressure: f64,
+    rest_area: f64,
+
+}
+
+impl Balloon {
+
+    /// `members` go around the ring in order, either way round.
+    pub fn new(members: Vec<ObjectHandle>, pressure: f64, rest_area: f64) -> Balloon {
+        Balloon {
+            members,
+            pressure,
+            rest_area,
+        }
+    }
+
+    /// Pushes the members given by `indices` (in the same order as [`Balloon::get_members`]) outwards.
+    pub(crate) fn apply_pressure(&self, objects: &mut [Circle], indices: &[usize]) {
+
+        let positions: Vec<Vector2<f64>> = indices.iter().map(|&index| objects[index].get_position()).collect();
+        let area = polygon_area(&positions);
+
+        if area == 0. {
+            return;
+        }
+
+        let pressure = self.pressure_at(area);
+
+        for member in 0..indices.len() {
+
+            let next = (member + 1) % indices.len();
+            let edge = positions[next] - positions[member];
+
+            // Rotating the edge a quarter turn away from the inside gives the outward normal scaled by its length.
+            let outward = Vector2::new(edge.y, -edge.x) * area.signum();
+            let force = outward * (pressure / 2.);
+
+            objects[indices[member]].force(force);
+            objects[indices[next]].force(force);
+
+        }
+
+    }
+
+    /// The gas pressure when the ring encloses `area`. Degenerate rings, with no area to speak of, have none.
+    fn pressure_at(&self, area: f64) -> f64 {
+
+        let area = area.abs().max(self.rest_area * MIN_AREA_FRACTION);
+
+        if !(area > 0.) || !area.is_finite() {
+            return 0.;
+        }
+
+        return self.pressure * self.rest_area / area;
+
+    }
+
+    pub fn get_members(&self) -> &Vec<ObjectHandle> {
+        &self.members
+    }
+
+    /// The gas pressure when the ring encloses its rest area.
+    pub fn get_pressure(&self) -> f64 {
+        self.pressure
+    }
+
+    pub fn set_pressure(&mut self, pressure: f64) {
+        self.pressure = pressure;
+    }
+
+    pub fn get_rest_area(&self) -> f64 {
+        self.rest_area
+    }
+
+    pub fn set_rest_area(&mut self, rest_area: f64) {
+        self.rest_area = rest_area;
+    }
+
+    /// The area the ring currently encloses.
+    pub fn get_area(&self, world: &PhysicsWorld) -> f64 {
+
+        let positions: Vec<Vector2<f64>> = self.members.iter()
+            .filter_map(|&handle| world.get_object(handle))
+            .map(|object| object.get_position())
+            .collect();
+
+        polygon_area(&positions).abs()
+
+    }
+
+    /// The gas pressure at the ring's current area.
+    pub fn get_current_pressure(&self, world: &PhysicsWorld) -> f64 {
+
+        self.pressure_at(self.get_area(world))
+
+    }
+
+}
+
+impl PhysicsWorld {
+
+    /// Builds a balloon out of `count` circles of `particle_radius` spaced evenly around a circle of `radius`,
+    /// joined to their neighbours by distance constraints of `stiffness`. The ring's members don't collide with each
+    /// other and its rest area is the area it starts with.
+    pub fn add_balloon_ring(&mut self, center: Vector2<f64>, radius: f64, count: usize, particle_radius: f64, particle_mass: f64, pressure: f64, stiffness: f64) -> Option<BalloonId> {
+
+        if count < 3 {
+            return None;
+        }
+
+        let collision_group = self.new_collision_group();
+        let mut members = Vec::new();
+
+        for member in 0..count {
+
+            let angle = 2. * PI * member as f64 / count as f64;
+            let mut circle = Circle::new(center + Vector2::new(angle.cos(), angle.sin()) * radius, particle_radius, particle_mass);
+            circle.set_collision_group(Some(collision_group));
+
+            members.push(self.push_object(circle, CircleShape::new(particle_radius as f32, 16)));
+
+        }
+
+        for member in 0..count {
+
+            let a = members[member];
+            let b = members[(member + 1) % count];
+            let rest_length = (self.get_object(b).unwrap().get_position() - self.get_object(a).unwrap().get_position()).magnitude();
+
+            self.add_distance_constraint(DistanceConstraint::new(a, b, rest_length, stiffness));
+
+        }
+
+        let positions: Vec<Vector2<f64>> = members.iter().map(|&handle| self.get_object(handle).unwrap().get_position()).collect();
+        let rest_area = polygon_area(&positions).abs();
+
+        self.add_balloon(Balloon::new(members, pressure, rest_area))
+
+    }
+
+}
@@ -0,0 +1,304 @@
+use cgmath::{InnerSpace, Vector2, Zero};
+use crate::force_generator::ForceGenerator;
+use crate::physics_object::PhysicsWorld;
+
+/// Nodes holding this many bodies or fewer aren't split any further.
+const LEAF_CAPACITY: usize = 8;
+
+/// Stops bodies stacked on the same spot from splitting nodes forever.
+const MAX_DEPTH: usize = 32;
+
+struct Node {
+    min: Vector2<f64>,
+    size: f64,
+    /// The sum of the weights below this node.
+    weight: f64,
+    /// The centre of the bodies below this node, weighted by the magnitude of their weights.
+    center: Vector2<f64>,
+    /// Index of the first of four children in `nodes`, or 0 for a leaf.
+    first_child: usize,
+    /// The range of `order` holding this node's bodies.
+    bodies: (usize, usize),
+}
+
+/// A quadtree over weighted points that approximates the inverse-square field they produce, Barnes–Hut style.
+///
+/// Weights may be negative (charges, say); far-away nodes then act as their net weight placed at the centre of the
+/// magnitudes below them.
+pub(crate) struct QuadTree {
+    nodes: Vec<Node>,
+    positions: Vec<Vector2<f64>>,
+    weights: Vec<f64>,
+    /// Indices into `positions`, grouped so that each node's bodies are contiguous.
+    order: Vec<usize>,
+}
+
+impl QuadTree {
+
+    /// Builds a tree over every point with a non-zero weight.
+    pub(crate) fn new(positions: Vec<Vector2<f64>>, weights: Vec<f64>) -> QuadTree {
+
+        let order: Vec<usize> = (0..positions.len()).filter(|&index| weights[index] != 0.).collect();
+
+        let mut min = Vector2::new(f64::INFINITY, f64::INFINITY);
+        let mut max = Vector2::new(f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+        for &index in order.iter() {
+            min.x = min.x.min(positions[index].x);
+            min.y = min.y.min(positions[index].y);
+            max.x = max.x.max(positions[index].x);
+            max.y = max.y.max(positions[index].y);
+        }
+
+        let mut tree = QuadTree {
+            nodes: Vec::new(),
+            positions,
+            weights,
+            order,
+        };
+
+        if tree.order.is_empty() {
+            return tree;
+        }
+
+        // Pad the root slightly so points on its far edge still fall inside it.
+        let size = (max.x - min.x).max(max.y - min.y).max(1e-6) * 1.0001;
+        let count = tree.order.len();
+
+        tree.nodes.push(Node {
+            min,
+            size,
+            weight: 0.,
+            center: Vector2::zero(),
+            first_child: 0,
+            bodies: (0, count),
+        });
+        tree.build(0, 0);
+
+        return tree;
+
+    }
+
+    fn build(&mut self, node_index: usize, depth: usize) {
+
+        let (start, end) = self.nodes[node_index].bodies;
+
+        if end - start > LEAF_CAPACITY && depth < MAX_DEPTH {
+
+            let half = self.nodes[node_index].size / 2.;
+            let middle = self.nodes[node_index].min + Vector2::new(half, half);
+
+            // Partition the bodies by row, then each row by column, giving the four quadrants in order.
+            let split_y = start + partition(&mut self.order[start..end], |index| self.positions[index].y < middle.y);
+            let split_top_x = start + partition(&mut self.order[start..split_y], |index| self.positions[index].x < middle.x);
+            let split_bottom_x = split_y + partition(&mut self.order[split_y..end], |index| self.positions[index].x < middle.x);
+
+            let ranges = [(start, split_top_x), (split_top_x, split_y), (split_y, split_bottom_x), (split_bottom_x, end)];
+            let first_child = self.nodes.len();
+            self.nodes[node_index].first_child = first_child;
+
+            for quadrant in 0..4 {
+
+                let min = self.nodes[node_index].min + Vector2::new((quadrant % 2) as f64 * half, (quadrant / 2) as f64 * half);
+
+                self.nodes.push(Node {
+                    min,
+                    size: half,
+                    weight: 0.,
+                    center: Vector2::zero(),
+                    first_child: 0,
+                    bodies: ranges[quadrant],
+                });
+
+            }
+
+            for quadrant in 0..4 {
+                self.build(first_child + quadrant, depth + 1);
+            }
+
+        }
+
+        let mut weight = 0.;
+        let mut magnitude = 0.;
+        let mut center = Vector2::zero();
+
+        for &index in self.order[start..end].iter() {
+            weight += self.weights[index];
+            magnitude += self.weights[index].abs();
+            center += self.positions[index] * self.weights[index].abs();
+        }
+
+        let node = &mut self.nodes[node_index];
+        node.weight = weight;
+        node.center = if magnitude > 0. { center / magnitude } else { node.min };
+
+    }
+
+    /// Sums `weight * (body - position) / (distance² + softening²)^(3/2)` over every body except `exclude`.
+    ///
+    /// Nodes narrower than `theta` times their distance from `position` are treated as a single body.
+    pub(crate) fn field_at(&self, position: Vector2<f64>, theta: f64, softening: f64, exclude: Option<usize>) -> Vector2<f64> {
+
+        let mut field = Vector2::zero();
+
+        if self.nodes.is_empty() {
+            return field;
+        }
+
+        let softening2 = softening * softening;
+        let mut stack = vec![0];
+
+        while let Some(node_index) = stack.pop() {
+
+            let node = &self.nodes[node_index];
+
+            if node.bodies.0 == node.bodies.1 {
+                continue;
+            }
+
+            let offset = node.center - position;
+            let distance2 = offset.magnitude2();
+
+            if node.first_child != 0 && node.size * node.size < theta * theta * distance2 {
+                field += offset * (node.weight / (distance2 + softening2).powf(1.5));
+                continue;
+            }
+
+            if node.first_child != 0 {
+                stack.extend(node.first_child..node.first_child + 4);
+                continue;
+            }
+
+            for &index in self.order[node.bodies.0..node.bodies.1].iter() {
+
+                if Some(index) == exclude {
+                    continue;
+                }
+
+                let offset = self.positions[index] - position;
+                let distance2 = offset.magnitude2() + softening2;
+
+                if distance2 > 0. {
+                    field += offset * (self.weights[index] / distance2.powf(1.5));
+                }
+
+            }
+
+        }
+
+        return field;
+
+    }
+
+}
+
+/// Moves every element matching `predicate` to the front of `slice` and returns how many there were.
+fn partition<F: Fn(usize) -> bool>(slice: &mut [usize], predicate: F) -> usize {
+
+    let mut split = 0;
+
+    for index in 0..slice.len() {
+        if predicate(slice[index]) {
+            slice.swap(split, index);
+            split += 1;
+        }
+    }
+
+    return split;
+
+}
+
+/// Evaluates `field` for every index below `count`, spread across the available cores.
+pub(crate) fn evaluate_in_parallel<F: Fn(usize) -> Vector2<f64> + Sync>(count: usize, field: F) -> Vec<Vector2<f64>> {
+
+    let mut results = vec![Vector2::zero(); count];
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = ((count + threads - 1) / threads).max(1);
+
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk) in results.chunks_mut(chunk_size).enumerate() {
+            let field = &field;
+            scope.spawn(move || {
+                for (offset, result) in chunk.iter_mut().enumerate() {
+                    *result = field(chunk_index * chunk_size + offset);
+                }
+            });
+        }
+    });
+
+    return results;
+
+}
+
+/// Makes every object attract every other in proportion to their masses, using a Barnes–Hut quadtree so large
+/// numbers of objects stay affordable.
+pub struct NBodyGravity {
+
+    gravitational_constant: f64,
+    theta: f64,
+    softening: f64,
+
+}
+
+impl NBodyGravity {
+
+    /// `theta` is the opening angle below which a group of distant bodies is approximated by its centre of mass,
+    /// and `softening` is a length added to every distance so close encounters don't produce huge forces.
+    pub fn new(gravitational_constant: f64, theta: f64, softening: f64) -> NBodyGravity {
+        NBodyGravity {
+            gravitational_constant,
+            theta,
+            softening,
+        }
+    }
+
+    pub fn get_gravitational_constant(&self) -> f64 {
+        self.gravitational_constant
+    }
+
+    pub fn set_gravitational_constant(&mut self, gravitational_constant: f64) {
+        self.gravitational_constant = gravitational_constant;
+    }
+
+    pub fn get_theta(&self) -> f64 {
+        self.theta
+    }
+
+    pub fn set_theta(&mut self, theta: f64) {
+        self.theta = theta;
+    }
+
+    pub fn get_softening(&self) -> f64 {
+        self.softening
+    }
+
+    pub fn set_softening(&mut self, softening: f64) {
+        self.softening = softening;
+    }
+
+}
+
+impl ForceGenerator for NBodyGravity {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, _d_t: f64) {
+
+        let objects = world.get_objects_mut_no_wake();
+        let tree = QuadTree::new(
+            objects.iter().map(|object| object.get_position()).collect(),
+            objects.iter().map(|object| object.get_mass()).collect());
+
+        let fields = evaluate_in_parallel(objects.len(), |index| {
+            if objects[index].is_sleeping() {
+                return Vector2::zero();
+            }
+            tree.field_at(objects[index].get_position(), self.theta, self.softening, Some(index))
+        });
+
+        for (object, field) in objects.iter_mut().zip(fields) {
+            let mass = object.get_mass();
+            object.force(field * (self.gravitational_constant * mass));
+        }
+
+    }
+
+}
@@ -0,0 +1,201 @@
+use std::f64::consts::PI;
+use cgmath::Vector2;
+use crate::force_generator::ForceGenerator;
+use crate::physics_object::PhysicsWorld;
+use crate::query::polygon_contains;
+
+/// How many points of each circle are tested against polygonal liquid to estimate how much of it is under.
+const POLYGON_SAMPLES: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LiquidRegion {
+    /// Everything below the horizontal line at `level` (with y increasing downwards, as on screen).
+    WaterLine { level: f64 },
+    /// Everything inside a simple polygon.
+    Polygon(Vec<Vector2<f64>>),
+}
+
+impl LiquidRegion {
+
+    pub fn contains(&self, point: Vector2<f64>) -> bool {
+
+        match self {
+            LiquidRegion::WaterLine { level } => point.y > *level,
+            LiquidRegion::Polygon(vertices) => polygon_contains(vertices, point),
+        }
+
+    }
+
+    /// How much of the circle of `radius` around `center` lies inside the region.
+    pub fn submerged_area(&self, center: Vector2<f64>, radius: f64) -> f64 {
+
+        match self {
+            LiquidRegion::WaterLine { level } => {
+
+                let depth = (center.y + radius - level).clamp(0., 2. * radius);
+                let from_center = radius - depth;
+
+                // The area of the circular segment below the line.
+                radius * radius * (from_center / radius).acos() - from_center * (2. * radius * depth - depth * depth).max(0.).sqrt()
+
+            }
+            LiquidRegion::Polygon(_) => {
+
+                // Sample the disc with a sunflower spiral, which covers it evenly.
+                let golden_angle = PI * (3. - 5f64.sqrt());
+                let mut inside = 0;
+
+                for sample in 0..POLYGON_SAMPLES {
+
+                    let distance = radius * ((sample as f64 + 0.5) / POLYGON_SAMPLES as f64).sqrt();
+                    let angle = sample as f64 * golden_angle;
+
+                    if self.contains(center + Vector2::new(angle.cos(), angle.sin()) * distance) {
+                        inside += 1;
+                    }
+
+                }
+
+                PI * radius * radius * inside as f64 / POLYGON_SAMPLES as f64
+
+            }
+        }
+
+    }
+
+}
+
+/// A body of still liquid with a density (mass per unit area) and a drag felt by anything in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiquidVolume {
+
+    region: LiquidRegion,
+    density: f64,
+    drag: f64,
+
+}
+
+impl LiquidVolume {
+
+    /// `drag` is the force per unit of velocity on a fully submerged object; partly submerged objects feel a share of it.
+    pub fn new(region: LiquidRegion, density: f64, drag: f64) -> LiquidVolume {
+        LiquidVolume {
+            region,
+            density,
+            drag,
+        }
+    }
+
+    pub fn get_region(&self) -> &LiquidRegion {
+        &self.region
+    }
+
+    pub fn set_region(&mut self, region: LiquidRegion) {
+        self.region = region;
+    }
+
+    pub fn get_density(&self) -> f64 {
+        self.density
+    }
+
+    pub fn set_density(&mut self, density: f64) {
+        self.density = density;
+    }
+
+    pub fn get_drag(&self) -> f64 {
+        self.drag
+    }
+
+    pub fn set_drag(&mut self, drag: f64) {
+        self.drag = drag;
+    }
+
+}
+
+/// Lifts objects in liquid by the weight of the liquid they displace and slows them down.
+///
+/// An object floats when its mass per unit area is below the liquid's density. `gravity` should match the
+/// acceleration given to [`UniformGravity`](crate::force_generator::UniformGravity).
+pub struct Buoyancy {
+
+    volumes: Vec<LiquidVolume>,
+    gravity: Vector2<f64>,
+
+}
+
+impl Buoyancy {
+
+    pub fn new(gravity: Vector2<f64>) -> Buoyancy {
+        Buoyancy {
+            volumes: Vec::new(),
+            gravity,
+        }
+    }
+
+    pub fn add_volume(&mut self, volume: LiquidVolume) -> usize {
+        self.volumes.push(volume);
+        self.volumes.len() - 1
+    }
+
+    pub fn get_volumes(&self) -> &Vec<LiquidVolume> {
+        &self.volumes
+    }
+
+    pub fn get_volumes_mut(&mut self) -> &mut Vec<LiquidVolume> {
+        &mut self.volumes
+    }
+
+    pub fn get_gravity(&self) -> Vector2<f64> {
+        self.gravity
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vector2<f64>) {
+        self.gravity = gravity;
+    }
+
+}
+
+impl ForceGenerator for Buoyancy {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, d_t: f64) {
+
+        for object in world.get_objects_mut_no_wake().iter_mut() {
+
+            let radius = object.get_size();
+            let area = PI * radius * radius;
+
+            // A resting object is woken once the liquid around it displaces more than its own mass, so that a
+            // rising level lifts it off the bottom.
+            if object.is_sleeping() {
+
+                let lift: f64 = self.volumes.iter()
+                    .map(|volume| volume.density * volume.region.submerged_area(object.get_position(), radius))
+                    .sum();
+
+                if lift <= object.get_mass() {
+                    continue;
+                }
+
+                object.wake();
+
+            }
+
+            for volume in self.volumes.iter() {
+
+                let submerged = volume.region.submerged_area(object.get_position(), radius);
+
+                if submerged <= 0. {
+                    continue;
+                }
+
+                let velocity = object.get_velocity() / d_t;
+                object.force(-self.gravity * (volume.density * submerged));
+                object.force(-velocity * (volume.drag * submerged / area));
+
+            }
+
+        }
+
+    }
+
+}
@@ -0,0 +1,203 @@
+use cgmath::{InnerSpace, Vector2};
+use crate::physics_object::PhysicsWorld;
+
+/// How many times a swept object may hit something and continue sliding within one update.
+const MAX_CCD_ITERATIONS: usize = 4;
+
+/// Gap left between a swept object and whatever it hit, so the next sweep doesn't start in contact.
+const CCD_SKIN: f64 = 1e-3;
+
+enum SweepHit {
+    Static,
+    Object(usize),
+}
+
+impl PhysicsWorld {
+
+    /// Sweeps an object from its previous position to its current one and stops it at the first static collider
+    /// or circle it would have passed through, continuing with the rest of its motion along the contact surface.
+    ///
+    /// Contacts are treated as perfectly inelastic along the normal, with momentum shared between colliding circles.
+    pub(crate) fn resolve_continuous_collisions(&mut self, index: usize) {
+
+        let handle = self.get_handles()[index];
+        let swept = self.get_objects()[index].clone();
+        let radius = self.get_objects()[index].get_size();
+        let mass = self.get_objects()[index].get_mass();
+        let mut start = self.get_objects()[index].get_prev_position();
+        let mut end = self.get_objects()[index].get_position();
+        let mut velocity = end - start;
+
+        for _ in 0..MAX_CCD_ITERATIONS {
+
+            let motion = end - start;
+            let distance = motion.magnitude();
+
+            if distance <= CCD_SKIN {
+                break;
+            }
+
+            let direction = motion / distance;
+            let mut earliest: Option<(f64, Vector2<f64>, SweepHit)> = None;
+
+            for collider in self.get_static_colliders() {
+                if let Some((fraction, normal)) = collider.sweep(start, end, radius) {
+                    if earliest.as_ref().map_or(true, |(earliest_distance, _, _)| fraction * distance < *earliest_distance) {
+                        earliest = Some((fraction * distance, normal, SweepHit::Static));
+                    }
+                }
+            }
+
+            if let Some(hit) = self.circle_cast(start, radius, direction, distance, &|other, object| other != handle && swept.collides_with(object)) {
+                if earliest.as_ref().map_or(true, |(earliest_distance, _, _)| hit.distance < *earliest_distance) {
+                    earliest = Some((hit.distance, hit.normal, SweepHit::Object(self.get_index(hit.handle).unwrap())));
+                }
+            }
+
+            let (hit_distance, normal, hit) = match earliest {
+                Some(earliest) => earliest,
+                None => break,
+            };
+
+            let contact_position = start + direction * (hit_distance - CCD_SKIN).max(0.);
+
+            match hit {
+                SweepHit::Static => {
+
+                    let approach = velocity.dot(normal);
+                    if approach < 0. {
+                        velocity -= normal * approach;
+                    }
+
+                }
+                SweepHit::Object(other_index) => {
+
+                    // `normal` points from the circle we hit towards the swept one.
+                    let other = &mut self.get_objects_mut_no_wake()[other_index];
+                    let other_velocity = other.get_velocity();
+                    let approach = velocity.dot(normal);
+                    let other_approach = other_velocity.dot(normal);
+
+                    if other.is_kinematic() {
+
+                        // Kinematic objects follow their motion whatever hits them, so they act as if infinitely heavy.
+                        if approach - other_approach < 0. {
+                            velocity += normal * (other_approach - approach);
+                        }
+
+                    } else {
+
+                        other.wake();
+                        let other_mass = other.get_mass();
+
+                        if approach - other_approach < 0. {
+                            let shared = (approach * mass + other_approach * other_mass) / (mass + other_mass);
+                            velocity += normal * (shared - approach);
+                            other.set_velocity(other_velocity + normal * (shared - other_approach));
+                        }
+
+                    }
+
+                }
+            }
+
+            let mut remaining = end - contact_position;
+            let into_surface = remaining.dot(normal);
+            if into_surface < 0. {
+                remaining -= normal * into_surface;
+            }
+
+            start = contact_position;
+            end = contact_position + remaining;
+
+        }
+
+        let object = &mut self.get_objects_mut_no_wake()[index];
+        object.set_raw_position(end);
+        object.set_prev_position(end - velocity);
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use sfml::graphics::CircleShape;
+    use crate::circle::Circle;
+    use crate::collider::StaticCollider;
+    use super::*;
+
+    fn moving(world: &mut PhysicsWorld, from: Vector2<f64>, to: Vector2<f64>) -> usize {
+
+        let mut circle = Circle::new(to, 2., 1.);
+        circle.set_prev_position(from);
+        circle.set_ccd_enabled(true);
+        world.push_object(circle, CircleShape::new(2., 8));
+
+        return world.get_objects().len() - 1;
+
+    }
+
+    #[test]
+    fn stops_at_a_thin_segment_instead_of_tunnelling() {
+
+        let mut world = PhysicsWorld::new(640, 640);
+        world.add_static_collider(StaticCollider::segment(Vector2::new(100., 0.), Vector2::new(100., 640.)));
+
+        let index = moving(&mut world, Vector2::new(90., 300.), Vector2::new(130., 300.));
+        world.resolve_continuous_collisions(index);
+
+        let object = &world.get_objects()[index];
+        assert!(object.get_position().x < 98. && object.get_position().x > 97.9);
+        assert!(object.get_velocity().x.abs() < 1e-9);
+
+    }
+
+    #[test]
+    fn slides_along_a_segment_it_hits_at_an_angle() {
+
+        let mut world = PhysicsWorld::new(640, 640);
+        world.add_static_collider(StaticCollider::segment(Vector2::new(100., 0.), Vector2::new(100., 640.)));
+
+        let index = moving(&mut world, Vector2::new(90., 300.), Vector2::new(130., 340.));
+        world.resolve_continuous_collisions(index);
+
+        let object = &world.get_objects()[index];
+        assert!(object.get_position().x < 98.);
+        assert!((object.get_position().y - 340.).abs() < 1e-6);
+        assert!((object.get_velocity() - Vector2::new(0., 40.)).magnitude() < 1e-9);
+
+    }
+
+    #[test]
+    fn shares_momentum_with_a_circle_it_hits() {
+
+        let mut world = PhysicsWorld::new(640, 640);
+        world.push_object(Circle::new(Vector2::new(100., 300.), 2., 1.), CircleShape::new(2., 8));
+
+        let index = moving(&mut world, Vector2::new(50., 300.), Vector2::new(150., 300.));
+        world.resolve_continuous_collisions(index);
+
+        assert!(world.get_objects()[index].get_position().x < 96.);
+        assert!((world.get_objects()[index].get_velocity() - Vector2::new(50., 0.)).magnitude() < 1e-9);
+        assert!((world.get_objects()[0].get_velocity() - Vector2::new(50., 0.)).magnitude() < 1e-9);
+
+    }
+
+    #[test]
+    fn kinematic_circles_are_not_pushed() {
+
+        let mut world = PhysicsWorld::new(640, 640);
+        world.push_object(Circle::new(Vector2::new(100., 300.), 2., 1.), CircleShape::new(2., 8));
+        world.get_objects_mut_no_wake()[0].set_kinematic(true);
+
+        let index = moving(&mut world, Vector2::new(50., 300.), Vector2::new(150., 300.));
+        world.resolve_continuous_collisions(index);
+
+        assert!(world.get_objects()[index].get_velocity().x.abs() < 1e-9);
+        assert_eq!(world.get_objects()[0].get_velocity(), Vector2::new(0., 0.));
+
+    }
+
+}
@@ -1,8 +1,10 @@
 use cgmath::{InnerSpace, MetricSpace, Vector2, Zero};
 use cgmath::num_traits::abs;
 use rand::{Rng, thread_rng};
-use sfml::graphics::{CircleShape, RenderTarget, RenderWindow, Shape, Transformable};
+use sfml::graphics::{CircleShape, Color, RenderTarget, RenderWindow, Shape, Transformable};
 use sfml::system::Vector2f;
+use crate::material::Material;
+use crate::thermal::REFERENCE_TEMPERATURE;
 
 pub struct Circle {
 
@@ -11,6 +13,23 @@ pub struct Circle {
     mass : f64,
     size : f64,
     force : Vector2<f64>,
+    ccd : bool,
+    sleeping : bool,
+    sleep_timer : f64,
+    charge : f64,
+    species : usize,
+    fluid : bool,
+    material : Material,
+    temperature : f64,
+    rest_size : f64,
+    angle : f64,
+    prev_angle : f64,
+    torque : f64,
+    collision_group : Option<u32>,
+    kinematic : bool,
+    age : f64,
+    lifetime : Option<f64>,
+    color : Option<Color>,
 
 }
 
@@ -23,9 +42,17 @@ impl Circle {
         self.position = new_position;
         self.force = Vector2::zero();
 
+        // Without inertia (no size or no mass) there is nothing to turn, so torque is dropped rather than divided by 0.
+        let angular_acceleration = if self.get_inertia() > 0. { self.torque / self.get_inertia() } else { 0. };
+        let new_angle = 2. * self.angle - self.prev_angle + angular_acceleration * (d_t * d_t);
+        self.prev_angle = self.angle;
+        self.angle = new_angle;
+        self.torque = 0.;
+
     }
 
-    pub fn resolve_collision(&mut self, collision_object: &mut Circle) {
+    /// Pushes two overlapping circles apart in proportion to their masses, returning whether they were touching.
+    pub fn resolve_collision(&mut self, collision_object: &mut Circle) -> bool {
 
 
         let mut resolution = (collision_object.get_position() - self.position);
@@ -41,12 +68,110 @@ impl Circle {
 
             distance = distance.sqrt();
 
-            resolution = resolution / distance * ((self.size + collision_object.get_size()) - distance);
+            let normal = resolution / distance;
+            let depth = (self.size + collision_object.get_size()) - distance;
+            resolution = normal * depth;
             let split = (self.mass + collision_object.get_mass());
 
             self.set_raw_position(-resolution * (collision_object.get_mass() / split) + self.position);
             collision_object.set_raw_position(resolution * (self.mass / split) + collision_object.get_position());
 
+            let friction = (self.material.get_friction() * collision_object.get_material().get_friction()).sqrt();
+
+            if friction > 0. {
+
+                let tangent = Vector2::new(-normal.y, normal.x);
+                let sliding = (collision_object.get_surface_velocity(-normal) - self.get_surface_velocity(normal)).dot(tangent);
+                let normal_impulse = depth / (1. / self.mass + 1. / collision_object.get_mass());
+                let limit = friction * normal_impulse;
+
+                let impulse = (-sliding / (self.get_tangential_inverse_mass() + collision_object.get_tangential_inverse_mass())).clamp(-limit, limit);
+
+                self.apply_contact_impulse(normal, -tangent * impulse);
+                collision_object.apply_contact_impulse(-normal, tangent * impulse);
+
+            }
+
+            return true;
+
+        }
+
+        return false;
+
+    }
+
+    /// Like [`Circle::resolve_collision`], but only this circle moves, as if `fixed` had infinite mass.
+    pub fn resolve_collision_with_fixed(&mut self, fixed: &Circle) -> bool {
+
+        let resolution = self.position - fixed.get_position();
+        let distance = resolution.magnitude();
+        let combined_size = self.size + fixed.get_size();
+
+        if distance == 0. || distance >= combined_size {
+            return false;
+        }
+
+        let normal = resolution / distance;
+        let depth = combined_size - distance;
+
+        self.set_raw_position(self.position + normal * depth);
+
+        let friction = (self.material.get_friction() * fixed.get_material().get_friction()).sqrt();
+        self.apply_friction(-normal, fixed.get_surface_velocity(normal), friction, depth);
+
+        return true;
+
+    }
+
+    /// Lets friction act on the point of the rim in `direction` (a unit vector from the centre), which rests against
+    /// an immovable surface moving at `surface_velocity` and was just pushed `depth` out of it. Sliding is cancelled
+    /// as far as `friction` allows, with the rest of the exchange going into spin, so a circle on a slope rolls.
+    pub(crate) fn apply_friction(&mut self, direction: Vector2<f64>, surface_velocity: Vector2<f64>, friction: f64, depth: f64) {
+
+        if friction <= 0. {
+            return;
+        }
+
+        let tangent = Vector2::new(-direction.y, direction.x);
+        let sliding = (self.get_surface_velocity(direction) - surface_velocity).dot(tangent);
+        let limit = friction * depth * self.mass;
+
+        let impulse = (-sliding / self.get_tangential_inverse_mass()).clamp(-limit, limit);
+        self.apply_contact_impulse(direction, tangent * impulse);
+
+    }
+
+    /// The velocity of the point on the rim in `direction` (a unit vector from the centre), spin included.
+    pub fn get_surface_velocity(&self, direction: Vector2<f64>) -> Vector2<f64> {
+
+        self.get_velocity() + Vector2::new(-direction.y, direction.x) * (self.get_angular_velocity() * self.size)
+
+    }
+
+    /// How much a unit impulse along the rim changes the rim's velocity, counting both the push and the spin it causes.
+    /// Objects without inertia don't spin, so only the push counts for them.
+    fn get_tangential_inverse_mass(&self) -> f64 {
+
+        if self.get_inertia() > 0. {
+            1. / self.mass + self.size * self.size / self.get_inertia()
+        } else {
+            1. / self.mass
+        }
+
+    }
+
+    /// Applies `impulse` at the point on the rim in `direction` (a unit vector from the centre), changing both the
+    /// velocity and the spin. Like velocities, impulses are per substep.
+    pub(crate) fn apply_contact_impulse(&mut self, direction: Vector2<f64>, impulse: Vector2<f64>) {
+
+        let arm = direction * self.size;
+
+        if self.mass > 0. {
+            self.prev_position -= impulse / self.mass;
+        }
+
+        if self.get_inertia() > 0. {
+            self.prev_angle -= (arm.x * impulse.y - arm.y * impulse.x) / self.get_inertia();
         }
 
     }
@@ -63,6 +188,13 @@ impl Circle {
 
     }
 
+    /// Like [`Circle::check_collision`], but for a circle of `radius` around `center` instead of a point.
+    pub fn check_circle_collision(&self, center : Vector2<f64>, radius : f64) -> bool {
+
+        (center - self.position).magnitude() < self.size + radius
+
+    }
+
     pub fn force(&mut self, force: Vector2<f64>) {
 
         self.force += force;
@@ -111,10 +243,211 @@ impl Circle {
         self.mass
     }
 
+    /// The moment of inertia of a solid disc with this object's mass and radius.
+    pub fn get_inertia(&self) -> f64 {
+        0.5 * self.mass * self.size * self.size
+    }
+
+    /// The orientation in radians, clockwise on screen.
+    pub fn get_angle(&self) -> f64 {
+        self.angle
+    }
+
+    /// Turns the object to `angle` without changing how fast it spins.
+    pub fn set_angle(&mut self, angle: f64) {
+        let angular_velocity = self.get_angular_velocity();
+
+        self.angle = angle;
+        self.prev_angle = angle - angular_velocity;
+    }
+
+    pub(crate) fn set_raw_angle(&mut self, angle: f64) {
+        self.angle = angle;
+    }
+
+    /// Like [`Circle::get_velocity`], the change in angle over the last update.
+    pub fn get_angular_velocity(&self) -> f64 {
+        self.angle - self.prev_angle
+    }
+
+    pub fn set_angular_velocity(&mut self, angular_velocity: f64) {
+        self.prev_angle = self.angle - angular_velocity;
+    }
+
+    pub fn torque(&mut self, torque: f64) {
+        self.torque += torque;
+    }
+
+    pub fn get_torque(&self) -> f64 {
+        self.torque
+    }
+
+    pub fn set_torque(&mut self, torque: f64) {
+        self.torque = torque;
+    }
+
+    pub fn set_mass(&mut self, mass: f64) {
+        self.mass = mass;
+    }
+
     pub fn get_size(&self) -> f64 {
         self.size
     }
 
+    /// Sets the radius at [`REFERENCE_TEMPERATURE`]. The current radius follows it, scaled for thermal expansion.
+    pub fn set_size(&mut self, size: f64) {
+        self.rest_size = size;
+        self.apply_thermal_expansion();
+    }
+
+    /// The radius this object has at [`REFERENCE_TEMPERATURE`].
+    pub fn get_rest_size(&self) -> f64 {
+        self.rest_size
+    }
+
+    pub fn get_temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    pub fn set_temperature(&mut self, temperature: f64) {
+        self.temperature = temperature;
+    }
+
+    /// Whether the object is at or above its material's melting point.
+    pub fn is_melted(&self) -> bool {
+        self.temperature >= self.material.get_melting_point()
+    }
+
+    /// Resizes the object for its current temperature.
+    pub(crate) fn apply_thermal_expansion(&mut self) {
+        self.size = self.rest_size * (1. + self.material.get_thermal_expansion() * (self.temperature - REFERENCE_TEMPERATURE)).max(0.);
+    }
+
+    /// Whether the world sweeps this object along its motion each update so it can't tunnel through thin geometry.
+    pub fn is_ccd_enabled(&self) -> bool {
+        self.ccd
+    }
+
+    pub fn set_ccd_enabled(&mut self, ccd: bool) {
+        self.ccd = ccd;
+    }
+
+    pub fn get_charge(&self) -> f64 {
+        self.charge
+    }
+
+    pub fn set_charge(&mut self, charge: f64) {
+        self.charge = charge;
+    }
+
+    pub fn get_species(&self) -> usize {
+        self.species
+    }
+
+    pub fn set_species(&mut self, species: usize) {
+        self.species = species;
+    }
+
+    /// Whether this object is a fluid particle. Fluid particles don't collide with each other; a fluid solver such
+    /// as [`SphFluid`](crate::fluid::SphFluid) keeps them apart instead.
+    pub fn is_fluid(&self) -> bool {
+        self.fluid
+    }
+
+    pub fn set_fluid(&mut self, fluid: bool) {
+        self.fluid = fluid;
+    }
+
+    /// Objects in the same collision group never collide with each other. Use
+    /// [`PhysicsWorld::new_collision_group`](crate::physics_object::PhysicsWorld::new_collision_group) for a group
+    /// no one else is using.
+    pub fn get_collision_group(&self) -> Option<u32> {
+        self.collision_group
+    }
+
+    pub fn set_collision_group(&mut self, collision_group: Option<u32>) {
+        self.collision_group = collision_group;
+    }
+
+    /// Whether contacts between this object and `other` are resolved at all.
+    pub fn collides_with(&self, other: &Circle) -> bool {
+        !(self.fluid && other.fluid) && (self.collision_group.is_none() || self.collision_group != other.collision_group)
+    }
+
+    pub fn get_material(&self) -> Material {
+        self.material
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    /// Whether the object follows a [`Motion`](crate::kinematic::Motion) instead of being moved by forces. Kinematic
+    /// objects push others around as if they had infinite mass.
+    pub fn is_kinematic(&self) -> bool {
+        self.kinematic
+    }
+
+    pub(crate) fn set_kinematic(&mut self, kinematic: bool) {
+        self.kinematic = kinematic;
+    }
+
+    /// How many seconds the object has been in the world.
+    pub fn get_age(&self) -> f64 {
+        self.age
+    }
+
+    /// Adds `d_t` seconds to the object's age, returning whether it has now outlived its lifetime.
+    pub(crate) fn advance_age(&mut self, d_t: f64) -> bool {
+        self.age += d_t;
+        self.lifetime.map_or(false, |lifetime| self.age >= lifetime)
+    }
+
+    /// How many seconds the object lasts before the world removes it. `None` keeps it for good.
+    pub fn get_lifetime(&self) -> Option<f64> {
+        self.lifetime
+    }
+
+    pub fn set_lifetime(&mut self, lifetime: Option<f64>) {
+        self.lifetime = lifetime;
+    }
+
+    /// The colour the object is drawn in, over whatever the draw predicate chose.
+    pub fn get_color(&self) -> Option<Color> {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Option<Color>) {
+        self.color = color;
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// How long this object has been moving slower than the world's sleep threshold.
+    pub fn get_sleep_timer(&self) -> f64 {
+        self.sleep_timer
+    }
+
+    pub(crate) fn set_sleep_timer(&mut self, sleep_timer: f64) {
+        self.sleep_timer = sleep_timer;
+    }
+
+    /// Stops the object where it is. The world won't integrate it again until it is woken.
+    pub(crate) fn sleep(&mut self) {
+        self.sleeping = true;
+        self.prev_position = self.position;
+        self.force = Vector2::zero();
+        self.prev_angle = self.angle;
+        self.torque = 0.;
+    }
+
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.sleep_timer = 0.;
+    }
+
     pub fn new(position : Vector2<f64>, radius : f64, mass: f64) -> Circle {
 
         Circle {
@@ -123,6 +456,23 @@ impl Circle {
             mass,
             size: radius,
             force: Vector2::new(0., 0.),
+            ccd: false,
+            sleeping: false,
+            sleep_timer: 0.,
+            charge: 0.,
+            species: 0,
+            fluid: false,
+            material: Material::new(),
+            temperature: REFERENCE_TEMPERATURE,
+            rest_size: radius,
+            angle: 0.,
+            prev_angle: 0.,
+            torque: 0.,
+            collision_group: None,
+            kinematic: false,
+            age: 0.,
+            lifetime: None,
+            color: None,
         }
 
     }
@@ -150,6 +500,23 @@ impl Clone for Circle {
             mass: self.mass,
             size: self.size,
             force: self.force.clone(),
+            ccd: self.ccd,
+            sleeping: self.sleeping,
+            sleep_timer: self.sleep_timer,
+            charge: self.charge,
+            species: self.species,
+            fluid: self.fluid,
+            material: self.material,
+            temperature: self.temperature,
+            rest_size: self.rest_size,
+            angle: self.angle,
+            prev_angle: self.prev_angle,
+            torque: self.torque,
+            collision_group: self.collision_group,
+            kinematic: self.kinematic,
+            age: self.age,
+            lifetime: self.lifetime,
+            color: self.color,
         }
     }
 
@@ -159,5 +526,22 @@ impl Clone for Circle {
         self.mass = source.mass;
         self.size = source.size;
         self.force = source.force.clone();
+        self.ccd = source.ccd;
+        self.sleeping = source.sleeping;
+        self.sleep_timer = source.sleep_timer;
+        self.charge = source.charge;
+        self.species = source.species;
+        self.fluid = source.fluid;
+        self.material = source.material;
+        self.temperature = source.temperature;
+        self.rest_size = source.rest_size;
+        self.angle = source.angle;
+        self.prev_angle = source.prev_angle;
+        self.torque = source.torque;
+        self.collision_group = source.collision_group;
+        self.kinematic = source.kinematic;
+        self.age = source.age;
+        self.lifetime = source.lifetime;
+        self.color = source.color;
     }
 }
\ No newline at end of file
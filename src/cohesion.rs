@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+use cgmath::InnerSpace;
+use crate::constraint::DistanceConstraint;
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+impl PhysicsWorld {
+
+    /// Bonds every touching pair of sticky objects that meet slowly enough and aren't bonded already.
+    ///
+    /// A bond's stiffness is the lower stickiness of the two materials, and it breaks once strained past the world's
+    /// breaking strain scaled by that same stickiness.
+    pub(crate) fn form_bonds(&mut self, contacts: &[(usize, usize)], d_t: f64) {
+
+        let speed_threshold = self.get_bond_speed_threshold();
+        let breaking_strain = self.get_bond_breaking_strain();
+        let mut bonded: HashSet<(ObjectHandle, ObjectHandle)> = self.get_bonded_pairs();
+        let mut new_bonds = Vec::new();
+
+        for &(a, b) in contacts {
+
+            let handle_a = self.get_handles()[a];
+            let handle_b = self.get_handles()[b];
+            let pair = (handle_a.min(handle_b), handle_a.max(handle_b));
+
+            if bonded.contains(&pair) {
+                continue;
+            }
+
+            let object_a = &self.get_objects()[a];
+            let object_b = &self.get_objects()[b];
+            let stickiness = object_a.get_material().get_stickiness().min(object_b.get_material().get_stickiness());
+
+            if stickiness <= 0. || object_a.is_melted() || object_b.is_melted() {
+                continue;
+            }
+
+            if (object_a.get_velocity() - object_b.get_velocity()).magnitude() >= speed_threshold * d_t {
+                continue;
+            }
+
+            let rest_length = object_a.get_size() + object_b.get_size();
+            let mut bond = DistanceConstraint::new(pair.0, pair.1, rest_length, stickiness.min(1.));
+            bond.set_breaking_strain(Some(breaking_strain * stickiness));
+
+            bonded.insert(pair);
+            new_bonds.push(bond);
+
+        }
+
+        for bond in new_bonds {
+            self.add_bond(bond);
+        }
+
+    }
+
+}
@@ -0,0 +1,332 @@
+use cgmath::{InnerSpace, Vector2, Zero};
+use crate::circle::Circle;
+use crate::kinematic::{Motion, Pose};
+
+/// Identifies a static collider in a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type StaticColliderId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColliderShape {
+    /// A wall between two points that circles collide with from either side.
+    Segment { start: Vector2<f64>, end: Vector2<f64> },
+    /// A fixed circle.
+    Circle { center: Vector2<f64>, radius: f64 },
+}
+
+impl ColliderShape {
+
+    fn map(&self, transform: impl Fn(Vector2<f64>) -> Vector2<f64>) -> ColliderShape {
+
+        match *self {
+            ColliderShape::Segment { start, end } => ColliderShape::Segment { start: transform(start), end: transform(end) },
+            ColliderShape::Circle { center, radius } => ColliderShape::Circle { center: transform(center), radius },
+        }
+
+    }
+
+}
+
+/// Immovable geometry that circles are pushed out of every update.
+#[derive(Clone, Debug)]
+pub struct StaticCollider {
+
+    shape: ColliderShape,
+    temperature: Option<f64>,
+    surface_speed: f64,
+    motion: Option<Motion>,
+    local_shape: ColliderShape,
+    pose: Pose,
+    prev_pose: Pose,
+
+}
+
+/// Returns the fraction of `motion` at which a point leaving `origin` first comes within `radius` of `center`.
+fn sweep_point_against_circle(origin: Vector2<f64>, motion: Vector2<f64>, center: Vector2<f64>, radius: f64) -> Option<f64> {
+
+    let offset = origin - center;
+    let a = motion.magnitude2();
+    let b = offset.dot(motion);
+    let c = offset.magnitude2() - radius * radius;
+
+    if a == 0. || c < 0. || b > 0. {
+        return None;
+    }
+
+    let discriminant = b * b - a * c;
+
+    if discriminant < 0. {
+        return None;
+    }
+
+    let fraction = (-b - discriminant.sqrt()) / a;
+
+    if fraction > 1. {
+        return None;
+    }
+
+    return Some(fraction);
+
+}
+
+impl StaticCollider {
+
+    pub fn new(shape: ColliderShape) -> StaticCollider {
+        StaticCollider {
+            shape,
+            temperature: None,
+            surface_speed: 0.,
+            motion: None,
+            local_shape: shape,
+            pose: Pose::new(Vector2::zero(), 0.),
+            prev_pose: Pose::new(Vector2::zero(), 0.),
+        }
+    }
+
+    pub fn segment(start: Vector2<f64>, end: Vector2<f64>) -> StaticCollider {
+        StaticCollider::new(ColliderShape::Segment { start, end })
+    }
+
+    pub fn circle(center: Vector2<f64>, radius: f64) -> StaticCollider {
+        StaticCollider::new(ColliderShape::Circle { center, radius })
+    }
+
+    pub fn get_shape(&self) -> ColliderShape {
+        self.shape
+    }
+
+    /// For a collider with a motion, the shape is taken to be where the motion puts it at the current pose.
+    pub fn set_shape(&mut self, shape: ColliderShape) {
+        self.shape = shape;
+        self.local_shape = shape.map(|point| self.pose.inverse_apply(point));
+    }
+
+    /// The path the collider follows, if it is kinematic rather than fixed in place.
+    pub fn get_motion(&self) -> Option<&Motion> {
+        self.motion.as_ref()
+    }
+
+    /// Makes the collider follow `motion`, taking its current shape as where it sits at time zero.
+    pub fn set_motion(&mut self, motion: Option<Motion>) {
+
+        self.pose = motion.as_ref().map_or(Pose::new(Vector2::zero(), 0.), |motion| motion.sample(0.));
+        self.prev_pose = self.pose;
+        self.local_shape = self.shape.map(|point| self.pose.inverse_apply(point));
+        self.motion = motion;
+
+    }
+
+    /// Moves the collider to where its motion puts it at `time`.
+    pub(crate) fn advance(&mut self, time: f64) {
+
+        if let Some(motion) = &self.motion {
+            self.prev_pose = self.pose;
+            self.pose = motion.sample(time);
+            let pose = self.pose;
+            self.shape = self.local_shape.map(|point| pose.apply(point));
+        }
+
+    }
+
+    /// Whether the collider's motion moved it during the last update.
+    pub fn is_moving(&self) -> bool {
+        self.motion.is_some() && self.pose != self.prev_pose
+    }
+
+    /// How far the part of the collider at `point` moved over the last update, in the same units as
+    /// [`Circle::get_velocity`]. Zero for colliders without a motion.
+    pub fn get_velocity_at(&self, point: Vector2<f64>) -> Vector2<f64> {
+
+        let local = self.pose.inverse_apply(point);
+        point - self.prev_pose.apply(local)
+
+    }
+
+    /// The fixed temperature of a hot or cold wall, which touching objects are heated or cooled towards.
+    /// `None` means the collider doesn't exchange heat.
+    pub fn get_temperature(&self) -> Option<f64> {
+        self.temperature
+    }
+
+    pub fn set_temperature(&mut self, temperature: Option<f64>) {
+        self.temperature = temperature;
+    }
+
+    /// How fast the surface slides along itself, in units per second, like a conveyor belt. It drags touching
+    /// objects along through friction, so it does nothing for frictionless ones. Segments move from `start` towards
+    /// `end` on both sides; circles turn clockwise on screen for a positive speed.
+    pub fn get_surface_speed(&self) -> f64 {
+        self.surface_speed
+    }
+
+    pub fn set_surface_speed(&mut self, surface_speed: f64) {
+        self.surface_speed = surface_speed;
+    }
+
+    /// The velocity of the surface where it touches along `normal`, from its motion and its surface speed, in the
+    /// same per-update units as [`Circle::get_velocity`].
+    fn get_surface_velocity(&self, point: Vector2<f64>, normal: Vector2<f64>, d_t: f64) -> Vector2<f64> {
+
+        let tangent = match self.shape {
+            ColliderShape::Segment { start, end } if end != start => (end - start).normalize(),
+            ColliderShape::Segment { .. } => Vector2::zero(),
+            ColliderShape::Circle { .. } => Vector2::new(-normal.y, normal.x),
+        };
+
+        self.get_velocity_at(point) + tangent * (self.surface_speed * d_t)
+
+    }
+
+    /// The point on the collider's surface (or centre line, for segments) closest to `point`.
+    pub fn closest_point(&self, point: Vector2<f64>) -> Vector2<f64> {
+
+        match self.shape {
+            ColliderShape::Segment { start, end } => {
+
+                let along = end - start;
+                let length2 = along.magnitude2();
+
+                if length2 == 0. {
+                    return start;
+                }
+
+                start + along * ((point - start).dot(along) / length2).clamp(0., 1.)
+
+            }
+            ColliderShape::Circle { center, radius } => {
+
+                let offset = point - center;
+
+                if offset.magnitude2() == 0. {
+                    return center + Vector2::new(radius, 0.);
+                }
+
+                center + offset.normalize_to(radius)
+
+            }
+        }
+
+    }
+
+    /// Returns the normal pointing out of the collider towards a circle at `position`, and how deep they overlap.
+    pub fn contact(&self, position: Vector2<f64>, radius: f64) -> Option<(Vector2<f64>, f64)> {
+
+        match self.shape {
+            ColliderShape::Segment { start, end } => {
+
+                let offset = position - self.closest_point(position);
+                let distance = offset.magnitude();
+
+                if distance >= radius {
+                    return None;
+                }
+
+                let normal = if distance > 0. {
+                    offset / distance
+                } else {
+                    let along = end - start;
+                    Vector2::new(-along.y, along.x).normalize()
+                };
+
+                Some((normal, radius - distance))
+
+            }
+            ColliderShape::Circle { center, radius: collider_radius } => {
+
+                let offset = position - center;
+                let distance = offset.magnitude();
+
+                if distance >= radius + collider_radius {
+                    return None;
+                }
+
+                let normal = if distance > 0. { offset / distance } else { Vector2::new(1., 0.) };
+
+                Some((normal, radius + collider_radius - distance))
+
+            }
+        }
+
+    }
+
+    /// Sweeps a circle of `radius` from `start` to `end` and returns the fraction of the way it got before touching,
+    /// along with the contact normal. Circles already touching at `start` are left to [`StaticCollider::resolve_collision`].
+    ///
+    /// Like collisions, sweeps against segments are two-sided: a circle is stopped from whichever side it comes.
+    pub fn sweep(&self, start: Vector2<f64>, end: Vector2<f64>, radius: f64) -> Option<(f64, Vector2<f64>)> {
+
+        let motion = end - start;
+
+        if self.contact(start, radius).is_some() {
+            return None;
+        }
+
+        let fraction = match self.shape {
+            ColliderShape::Segment { start: segment_start, end: segment_end } => {
+
+                let along = segment_end - segment_start;
+                let mut earliest = sweep_point_against_circle(start, motion, segment_start, radius);
+
+                if let Some(fraction) = sweep_point_against_circle(start, motion, segment_end, radius) {
+                    earliest = Some(earliest.map_or(fraction, |earliest| earliest.min(fraction)));
+                }
+
+                if along.magnitude2() > 0. {
+
+                    // Test against the face of the segment's capsule that points at the start position.
+                    let mut normal = Vector2::new(-along.y, along.x).normalize();
+                    if (start - segment_start).dot(normal) < 0. {
+                        normal = -normal;
+                    }
+
+                    let approach = motion.dot(normal);
+
+                    if approach < 0. {
+
+                        let fraction = ((start - segment_start).dot(normal) - radius) / -approach;
+                        let along_fraction = (start + motion * fraction - segment_start).dot(along) / along.magnitude2();
+
+                        if (0. ..=1.).contains(&fraction) && (0. ..=1.).contains(&along_fraction) {
+                            earliest = Some(earliest.map_or(fraction, |earliest| earliest.min(fraction)));
+                        }
+
+                    }
+
+                }
+
+                earliest
+
+            }
+            ColliderShape::Circle { center, radius: collider_radius } => {
+                sweep_point_against_circle(start, motion, center, radius + collider_radius)
+            }
+        }?;
+
+        let contact_position = start + motion * fraction;
+        let offset = contact_position - self.closest_point(contact_position);
+
+        let normal = match self.shape {
+            ColliderShape::Circle { center, .. } => (contact_position - center).normalize(),
+            ColliderShape::Segment { .. } => offset.normalize(),
+        };
+
+        return Some((fraction, normal));
+
+    }
+
+    /// Pushes `object` out of the collider, the same way [`Circle::resolve_collision`] separates two circles, and lets
+    /// the object's friction act on it over the update of `d_t` seconds, returning whether they were touching.
+    pub fn resolve_collision(&self, object: &mut Circle, d_t: f64) -> bool {
+
+        if let Some((normal, depth)) = self.contact(object.get_position(), object.get_size()) {
+            object.set_raw_position(object.get_position() + normal * depth);
+            let friction = object.get_material().get_friction();
+            let surface_velocity = self.get_surface_velocity(object.get_position() - normal * object.get_size(), normal, d_t);
+            object.apply_friction(-normal, surface_velocity, friction, depth);
+            return true;
+        }
+
+        return false;
+
+    }
+
+}
@@ -0,0 +1,114 @@
+use cgmath::InnerSpace;
+use crate::circle::Circle;
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// Identifies a distance constraint added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type ConstraintId = usize;
+
+/// Keeps two objects a set distance apart, like [`Circle::connect`] but weighted by mass and optionally soft or breakable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceConstraint {
+
+    a: ObjectHandle,
+    b: ObjectHandle,
+    rest_length: f64,
+    stiffness: f64,
+    breaking_strain: Option<f64>,
+
+}
+
+impl DistanceConstraint {
+
+    /// `stiffness` is the fraction of the error corrected each update, from 0 to 1.
+    pub fn new(a: ObjectHandle, b: ObjectHandle, rest_length: f64, stiffness: f64) -> DistanceConstraint {
+        DistanceConstraint {
+            a,
+            b,
+            rest_length,
+            stiffness,
+            breaking_strain: None,
+        }
+    }
+
+    pub fn get_objects(&self) -> (ObjectHandle, ObjectHandle) {
+        (self.a, self.b)
+    }
+
+    pub fn get_rest_length(&self) -> f64 {
+        self.rest_length
+    }
+
+    pub fn set_rest_length(&mut self, rest_length: f64) {
+        self.rest_length = rest_length;
+    }
+
+    pub fn get_stiffness(&self) -> f64 {
+        self.stiffness
+    }
+
+    pub fn set_stiffness(&mut self, stiffness: f64) {
+        self.stiffness = stiffness;
+    }
+
+    /// How far the constraint can be stretched or squashed, as a fraction of its rest length, before the world
+    /// removes it. `None` means it never breaks.
+    pub fn get_breaking_strain(&self) -> Option<f64> {
+        self.breaking_strain
+    }
+
+    pub fn set_breaking_strain(&mut self, breaking_strain: Option<f64>) {
+        self.breaking_strain = breaking_strain;
+    }
+
+    /// How far the objects currently are from the rest length, as a fraction of it.
+    pub fn get_strain(&self, world: &PhysicsWorld) -> f64 {
+
+        match (world.get_object(self.a), world.get_object(self.b)) {
+            (Some(a), Some(b)) => self.strain_between(a, b),
+            _ => 0.,
+        }
+
+    }
+
+    pub(crate) fn strain_between(&self, a: &Circle, b: &Circle) -> f64 {
+
+        if self.rest_length == 0. {
+            return 0.;
+        }
+
+        ((b.get_position() - a.get_position()).magnitude() - self.rest_length).abs() / self.rest_length
+
+    }
+
+    /// Moves both objects towards the rest length, the lighter one further.
+    pub(crate) fn solve_between(&self, a: &mut Circle, b: &mut Circle) {
+
+        let offset = b.get_position() - a.get_position();
+        let length = offset.magnitude();
+
+        if length == 0. {
+            return;
+        }
+
+        // Kinematic objects follow their motion, so the other end takes all of the correction.
+        let inverse_mass_a = if a.is_kinematic() { 0. } else { 1. / a.get_mass() };
+        let inverse_mass_b = if b.is_kinematic() { 0. } else { 1. / b.get_mass() };
+
+        if inverse_mass_a + inverse_mass_b == 0. {
+            return;
+        }
+        let correction = offset / length * ((length - self.rest_length) * self.stiffness / (inverse_mass_a + inverse_mass_b));
+
+        a.set_raw_position(a.get_position() + correction * inverse_mass_a);
+        b.set_raw_position(b.get_position() - correction * inverse_mass_b);
+
+    }
+
+}
+
+pub(crate) struct DistanceConstraintEntry {
+    pub(crate) id: ConstraintId,
+    pub(crate) constraint: DistanceConstraint,
+    /// Whether the world made this constraint itself when two sticky objects touched.
+    pub(crate) bond: bool,
+}
@@ -0,0 +1,165 @@
+use cgmath::{InnerSpace, Vector2, Zero};
+use crate::barnes_hut::{evaluate_in_parallel, QuadTree};
+use crate::force_generator::ForceGenerator;
+use crate::physics_object::PhysicsWorld;
+
+/// Pushes objects with like charges apart and pulls opposite charges together with an inverse-square force.
+///
+/// With a cutoff, only pairs found through the world's broadphase within that distance interact, which suits
+/// screened, crystal-like setups. Without one, every charge feels every other through a Barnes–Hut tree.
+pub struct Coulomb {
+
+    coulomb_constant: f64,
+    cutoff: Option<f64>,
+    softening: f64,
+    theta: f64,
+
+}
+
+impl Coulomb {
+
+    pub fn new(coulomb_constant: f64, cutoff: Option<f64>, softening: f64) -> Coulomb {
+        Coulomb {
+            coulomb_constant,
+            cutoff,
+            softening,
+            theta: 0.5,
+        }
+    }
+
+    pub fn get_coulomb_constant(&self) -> f64 {
+        self.coulomb_constant
+    }
+
+    pub fn set_coulomb_constant(&mut self, coulomb_constant: f64) {
+        self.coulomb_constant = coulomb_constant;
+    }
+
+    pub fn get_cutoff(&self) -> Option<f64> {
+        self.cutoff
+    }
+
+    pub fn set_cutoff(&mut self, cutoff: Option<f64>) {
+        self.cutoff = cutoff;
+    }
+
+    pub fn get_softening(&self) -> f64 {
+        self.softening
+    }
+
+    pub fn set_softening(&mut self, softening: f64) {
+        self.softening = softening;
+    }
+
+    /// The Barnes–Hut opening angle used when there is no cutoff.
+    pub fn get_theta(&self) -> f64 {
+        self.theta
+    }
+
+    pub fn set_theta(&mut self, theta: f64) {
+        self.theta = theta;
+    }
+
+}
+
+impl ForceGenerator for Coulomb {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, _d_t: f64) {
+
+        match self.cutoff {
+            Some(cutoff) => {
+
+                let pairs = world.query_index_pairs(cutoff);
+                let objects = world.get_objects_mut_no_wake();
+                let softening2 = self.softening * self.softening;
+
+                for (a, b) in pairs {
+
+                    let charge_product = objects[a].get_charge() * objects[b].get_charge();
+
+                    if charge_product == 0. {
+                        continue;
+                    }
+
+                    let offset = objects[a].get_position() - objects[b].get_position();
+                    let distance2 = offset.magnitude2() + softening2;
+
+                    if distance2 == 0. {
+                        continue;
+                    }
+
+                    let force = offset * (self.coulomb_constant * charge_product / (distance2 * distance2.sqrt()));
+                    objects[a].force(force);
+                    objects[b].force(-force);
+
+                }
+
+            }
+            None => {
+
+                let objects = world.get_objects_mut_no_wake();
+                let tree = QuadTree::new(
+                    objects.iter().map(|object| object.get_position()).collect(),
+                    objects.iter().map(|object| object.get_charge()).collect());
+
+                let fields = evaluate_in_parallel(objects.len(), |index| {
+                    if objects[index].is_sleeping() || objects[index].get_charge() == 0. {
+                        return Vector2::zero();
+                    }
+                    tree.field_at(objects[index].get_position(), self.theta, self.softening, Some(index))
+                });
+
+                for (object, field) in objects.iter_mut().zip(fields) {
+                    let charge = object.get_charge();
+                    object.force(-field * (self.coulomb_constant * charge));
+                }
+
+            }
+        }
+
+    }
+
+}
+
+/// Bends the paths of moving charges with the Lorentz force from a uniform field pointing out of the screen.
+pub struct UniformMagneticField {
+
+    strength: f64,
+
+}
+
+impl UniformMagneticField {
+
+    pub fn new(strength: f64) -> UniformMagneticField {
+        UniformMagneticField {
+            strength,
+        }
+    }
+
+    pub fn get_strength(&self) -> f64 {
+        self.strength
+    }
+
+    pub fn set_strength(&mut self, strength: f64) {
+        self.strength = strength;
+    }
+
+}
+
+impl ForceGenerator for UniformMagneticField {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, d_t: f64) {
+
+        for object in world.get_objects_mut_no_wake().iter_mut() {
+
+            let velocity = object.get_velocity() / d_t;
+            let charge = object.get_charge();
+
+            // q v × B, with B along the axis pointing out of the screen.
+            object.force(Vector2::new(velocity.y, -velocity.x) * (charge * self.strength));
+
+        }
+
+    }
+
+}
@@ -0,0 +1,278 @@
+use std::f64::consts::PI;
+use cgmath::{InnerSpace, Vector2};
+use rand::Rng;
+use sfml::graphics::Color;
+use crate::circle::Circle;
+use crate::material::Material;
+use crate::query::Aabb;
+
+/// Identifies an emitter added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type EmitterId = usize;
+
+/// Identifies a sink added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type SinkId = usize;
+
+/// Where an emitter spawns objects or a sink swallows them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Region {
+    Point(Vector2<f64>),
+    Circle { center: Vector2<f64>, radius: f64 },
+    Aabb(Aabb),
+}
+
+impl Region {
+
+    /// Whether `point` is in the region. Nothing is ever in a point.
+    pub fn contains(&self, point: Vector2<f64>) -> bool {
+
+        match self {
+            Region::Point(_) => false,
+            Region::Circle { center, radius } => (point - center).magnitude2() <= radius * radius,
+            Region::Aabb(aabb) => aabb.contains(point),
+        }
+
+    }
+
+    /// A point spread evenly over the region.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Vector2<f64> {
+
+        match self {
+            Region::Point(point) => *point,
+            Region::Circle { center, radius } => {
+
+                let distance = radius * rng.gen_range(0.0..=1.0f64).sqrt();
+                let angle = rng.gen_range(0.0..2. * PI);
+                center + Vector2::new(angle.cos(), angle.sin()) * distance
+
+            }
+            Region::Aabb(aabb) => Vector2::new(sample_range(rng, (aabb.min.x, aabb.max.x)), sample_range(rng, (aabb.min.y, aabb.max.y))),
+        }
+
+    }
+
+}
+
+fn sample_range<R: Rng>(rng: &mut R, (min, max): (f64, f64)) -> f64 {
+
+    if min >= max {
+        return min;
+    }
+
+    return rng.gen_range(min..max);
+
+}
+
+/// Spawns objects over a region at a steady rate and in bursts.
+///
+/// Each object gets a random speed, radius and mass from the emitter's ranges and is sent off in a random direction
+/// within `spread` radians either side of `direction`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emitter {
+
+    region: Region,
+    rate: f64,
+    pending_burst: usize,
+    direction: f64,
+    spread: f64,
+    speed: (f64, f64),
+    radius: (f64, f64),
+    mass: (f64, f64),
+    lifetime: Option<f64>,
+    material: Material,
+    color: Option<Color>,
+    enabled: bool,
+    accumulator: f64,
+
+}
+
+impl Emitter {
+
+    /// An emitter of still circles of radius 4 and mass 1, `rate` times a second, until configured otherwise.
+    pub fn new(region: Region, rate: f64) -> Emitter {
+        Emitter {
+            region,
+            rate,
+            pending_burst: 0,
+            direction: 0.,
+            spread: PI,
+            speed: (0., 0.),
+            radius: (4., 4.),
+            mass: (1., 1.),
+            lifetime: None,
+            material: Material::new(),
+            color: None,
+            enabled: true,
+            accumulator: 0.,
+        }
+    }
+
+    /// How many objects to spawn this update: what the rate has built up to, plus any burst asked for.
+    pub(crate) fn take_spawn_count(&mut self, d_t: f64) -> usize {
+
+        if self.enabled {
+            self.accumulator += self.rate * d_t;
+        }
+
+        let steady = self.accumulator.floor();
+        self.accumulator -= steady;
+
+        let count = steady as usize + self.pending_burst;
+        self.pending_burst = 0;
+
+        return count;
+
+    }
+
+    pub(crate) fn spawn<R: Rng>(&self, rng: &mut R, d_t: f64) -> Circle {
+
+        let angle = self.direction + if self.spread > 0. { rng.gen_range(-self.spread..=self.spread) } else { 0. };
+        let speed = sample_range(rng, self.speed);
+
+        let mut circle = Circle::new(self.region.sample(rng), sample_range(rng, self.radius), sample_range(rng, self.mass));
+        circle.set_velocity(Vector2::new(angle.cos(), angle.sin()) * (speed * d_t));
+        circle.set_material(self.material);
+        circle.set_lifetime(self.lifetime);
+        circle.set_color(self.color);
+
+        return circle;
+
+    }
+
+    /// Spawns `count` extra objects on the next update, whether or not the emitter is enabled.
+    pub fn burst(&mut self, count: usize) {
+        self.pending_burst += count;
+    }
+
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    /// Objects spawned per second while enabled.
+    pub fn get_rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+    }
+
+    /// The angle, in radians, objects are sent off at, clockwise from the x axis on screen.
+    pub fn get_direction(&self) -> f64 {
+        self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: f64) {
+        self.direction = direction;
+    }
+
+    /// How far, in radians, objects may stray either side of the direction. π sends them every way.
+    pub fn get_spread(&self) -> f64 {
+        self.spread
+    }
+
+    pub fn set_spread(&mut self, spread: f64) {
+        self.spread = spread;
+    }
+
+    /// The lowest and highest starting speed, in units per second.
+    pub fn get_speed(&self) -> (f64, f64) {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, min: f64, max: f64) {
+        self.speed = (min, max);
+    }
+
+    pub fn get_radius(&self) -> (f64, f64) {
+        self.radius
+    }
+
+    pub fn set_radius(&mut self, min: f64, max: f64) {
+        self.radius = (min, max);
+    }
+
+    pub fn get_mass(&self) -> (f64, f64) {
+        self.mass
+    }
+
+    pub fn set_mass(&mut self, min: f64, max: f64) {
+        self.mass = (min, max);
+    }
+
+    /// How many seconds spawned objects last before they are removed. `None` keeps them for good.
+    pub fn get_lifetime(&self) -> Option<f64> {
+        self.lifetime
+    }
+
+    pub fn set_lifetime(&mut self, lifetime: Option<f64>) {
+        self.lifetime = lifetime;
+    }
+
+    pub fn get_material(&self) -> Material {
+        self.material
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    /// The colour spawned objects are drawn in, over whatever the draw predicate chose. `None` leaves it to the predicate.
+    pub fn get_color(&self) -> Option<Color> {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Option<Color>) {
+        self.color = color;
+    }
+
+    /// Whether the emitter spawns at its rate. Bursts happen either way.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+}
+
+/// Removes every object whose centre enters its region.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sink {
+
+    region: Region,
+    removed_count: usize,
+
+}
+
+impl Sink {
+
+    pub fn new(region: Region) -> Sink {
+        Sink {
+            region,
+            removed_count: 0,
+        }
+    }
+
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    /// How many objects the sink has removed so far.
+    pub fn get_removed_count(&self) -> usize {
+        self.removed_count
+    }
+
+    pub(crate) fn count_removed(&mut self) {
+        self.removed_count += 1;
+    }
+
+}
@@ -0,0 +1,9 @@
+use cgmath::Vector2;
+use crate::physics_object::ObjectHandle;
+
+/// Something notable that happened during an update, for the renderer or game code to react to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhysicsEvent {
+    /// `absorbed` merged into `survivor`, which now sits at `position`. `absorbed` no longer exists.
+    Merged { survivor: ObjectHandle, absorbed: ObjectHandle, position: Vector2<f64> },
+}
@@ -0,0 +1,226 @@
+use cgmath::{InnerSpace, Vector2, Zero};
+use sfml::graphics::Image;
+use crate::force_generator::ForceGenerator;
+use crate::noise::Perlin;
+use crate::physics_object::PhysicsWorld;
+
+/// Flow velocities stored on a regular grid and interpolated bilinearly between cells.
+#[derive(Clone, Debug)]
+pub struct FlowGrid {
+
+    origin: Vector2<f64>,
+    cell_size: f64,
+    width: usize,
+    height: usize,
+    velocities: Vec<Vector2<f64>>,
+
+}
+
+impl FlowGrid {
+
+    /// A still grid of `width` by `height` samples, `cell_size` apart, with the first at `origin`.
+    pub fn new(origin: Vector2<f64>, cell_size: f64, width: usize, height: usize) -> FlowGrid {
+        FlowGrid {
+            origin,
+            cell_size,
+            width,
+            height,
+            velocities: vec![Vector2::zero(); width * height],
+        }
+    }
+
+    /// Reads velocities from an image's red and green channels, where 128 is still and 0 and 255 are
+    /// `-max_speed` and `max_speed`. Each pixel becomes one sample.
+    pub fn from_image(path: &str, origin: Vector2<f64>, cell_size: f64, max_speed: f64) -> Option<FlowGrid> {
+
+        let image = Image::from_file(path)?;
+        let size = image.size();
+        let mut grid = FlowGrid::new(origin, cell_size, size.x as usize, size.y as usize);
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let color = image.pixel_at(x, y);
+                grid.set_velocity(x as usize, y as usize, Vector2::new(
+                    (color.red() as f64 / 127.5 - 1.) * max_speed,
+                    (color.green() as f64 / 127.5 - 1.) * max_speed));
+            }
+        }
+
+        Some(grid)
+
+    }
+
+    pub fn get_velocity(&self, x: usize, y: usize) -> Vector2<f64> {
+        self.velocities[x + y * self.width]
+    }
+
+    pub fn set_velocity(&mut self, x: usize, y: usize, velocity: Vector2<f64>) {
+        self.velocities[x + y * self.width] = velocity;
+    }
+
+    /// The interpolated velocity at `position`, holding the edge values outside the grid.
+    pub fn sample(&self, position: Vector2<f64>) -> Vector2<f64> {
+
+        if self.width == 0 || self.height == 0 {
+            return Vector2::zero();
+        }
+
+        let local = (position - self.origin) / self.cell_size;
+        let x = local.x.clamp(0., (self.width - 1) as f64);
+        let y = local.y.clamp(0., (self.height - 1) as f64);
+
+        let x0 = x.floor() as usize;
+        let y0 = y.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let tx = x - x0 as f64;
+        let ty = y - y0 as f64;
+
+        let top = self.get_velocity(x0, y0) * (1. - tx) + self.get_velocity(x1, y0) * tx;
+        let bottom = self.get_velocity(x0, y1) * (1. - tx) + self.get_velocity(x1, y1) * tx;
+
+        top * (1. - ty) + bottom * ty
+
+    }
+
+}
+
+/// A flow that wanders smoothly through space and time, built from Perlin noise.
+#[derive(Clone, Debug)]
+pub struct NoiseFlow {
+
+    noise: Perlin,
+    scale: f64,
+    speed: f64,
+    strength: f64,
+    curl: bool,
+
+}
+
+impl NoiseFlow {
+
+    /// `scale` is the size of a typical swirl in world units, `speed` how fast the pattern changes per second and
+    /// `strength` the typical flow speed. Curl noise has no sources or sinks, so it stirs objects without bunching them up;
+    /// its `strength` is that of the stream function it is the curl of, so the flow itself is about `strength / scale`.
+    pub fn new(seed: u64, scale: f64, speed: f64, strength: f64, curl: bool) -> NoiseFlow {
+        NoiseFlow {
+            noise: Perlin::new(seed),
+            scale,
+            speed,
+            strength,
+            curl,
+        }
+    }
+
+    pub fn sample(&self, position: Vector2<f64>, time: f64) -> Vector2<f64> {
+
+        let x = position.x / self.scale;
+        let y = position.y / self.scale;
+        let z = time * self.speed;
+
+        if self.curl {
+
+            // Take the curl of the noise as a stream function, by central differences.
+            let step = 1e-3;
+            let d_dx = (self.noise.sample(x + step, y, z) - self.noise.sample(x - step, y, z)) / (2. * step);
+            let d_dy = (self.noise.sample(x, y + step, z) - self.noise.sample(x, y - step, z)) / (2. * step);
+
+            // The differences are taken in noise space, so scale them back into world units.
+            return Vector2::new(d_dy, -d_dx) * (self.strength / self.scale);
+
+        }
+
+        // Offset the second channel far enough that it is unrelated to the first.
+        Vector2::new(self.noise.sample(x, y, z), self.noise.sample(x + 31.7, y + 47.3, z + 11.1)) * self.strength
+
+    }
+
+}
+
+pub enum VectorField {
+    /// Velocity from a closure of position and time in seconds.
+    Analytic(Box<dyn Fn(Vector2<f64>, f64) -> Vector2<f64>>),
+    Grid(FlowGrid),
+    Noise(NoiseFlow),
+}
+
+impl VectorField {
+
+    pub fn sample(&self, position: Vector2<f64>, time: f64) -> Vector2<f64> {
+
+        match self {
+            VectorField::Analytic(field) => field(position, time),
+            VectorField::Grid(grid) => grid.sample(position),
+            VectorField::Noise(noise) => noise.sample(position, time),
+        }
+
+    }
+
+}
+
+/// Drags every object towards the local velocity of a flow, like wind or a current. Time-varying fields are sampled
+/// against the world's clock.
+pub struct FlowField {
+
+    field: VectorField,
+    drag: f64,
+
+}
+
+impl FlowField {
+
+    /// `drag` is the force per unit of velocity difference between an object and the flow around it.
+    pub fn new(field: VectorField, drag: f64) -> FlowField {
+        FlowField {
+            field,
+            drag,
+        }
+    }
+
+    pub fn get_field(&self) -> &VectorField {
+        &self.field
+    }
+
+    pub fn set_field(&mut self, field: VectorField) {
+        self.field = field;
+    }
+
+    pub fn get_drag(&self) -> f64 {
+        self.drag
+    }
+
+    pub fn set_drag(&mut self, drag: f64) {
+        self.drag = drag;
+    }
+
+}
+
+impl ForceGenerator for FlowField {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, d_t: f64) {
+
+        let time = world.get_time();
+        let threshold = world.get_sleep_velocity_threshold();
+        let time_to_sleep = world.get_time_to_sleep();
+
+        for object in world.get_objects_mut_no_wake().iter_mut() {
+
+            let flow = self.field.sample(object.get_position(), time);
+            let velocity = object.get_velocity() / d_t;
+            let force = (flow - velocity) * self.drag;
+
+            // A resting object only wakes for a flow strong enough to get it moving before it could settle again.
+            if object.is_sleeping() {
+                if force.magnitude() / object.get_mass() * time_to_sleep <= threshold {
+                    continue;
+                }
+                object.wake();
+            }
+
+            object.force(force);
+
+        }
+
+    }
+
+}
@@ -0,0 +1,173 @@
+use std::f64::consts::PI;
+use cgmath::{InnerSpace, Vector2, Zero};
+use crate::force_generator::ForceGenerator;
+use crate::physics_object::PhysicsWorld;
+
+/// A smoothed-particle hydrodynamics solver for every object marked with [`Circle::set_fluid`](crate::circle::Circle::set_fluid).
+///
+/// Fluid particles find their neighbours through the world's grid and are pushed by pressure, viscosity and surface
+/// tension forces. They still collide normally with every non-fluid object.
+pub struct SphFluid {
+
+    smoothing_radius: f64,
+    rest_density: f64,
+    stiffness: f64,
+    viscosity: f64,
+    surface_tension: f64,
+
+}
+
+impl SphFluid {
+
+    /// `smoothing_radius` is how far each particle's influence reaches; a few particle diameters works well.
+    pub fn new(smoothing_radius: f64, rest_density: f64, stiffness: f64, viscosity: f64, surface_tension: f64) -> SphFluid {
+        SphFluid {
+            smoothing_radius,
+            rest_density,
+            stiffness,
+            viscosity,
+            surface_tension,
+        }
+    }
+
+    pub fn get_smoothing_radius(&self) -> f64 {
+        self.smoothing_radius
+    }
+
+    pub fn set_smoothing_radius(&mut self, smoothing_radius: f64) {
+        self.smoothing_radius = smoothing_radius;
+    }
+
+    /// The mass per unit area the fluid settles at.
+    pub fn get_rest_density(&self) -> f64 {
+        self.rest_density
+    }
+
+    pub fn set_rest_density(&mut self, rest_density: f64) {
+        self.rest_density = rest_density;
+    }
+
+    /// How much pressure builds up per unit of density above the rest density.
+    pub fn get_stiffness(&self) -> f64 {
+        self.stiffness
+    }
+
+    pub fn set_stiffness(&mut self, stiffness: f64) {
+        self.stiffness = stiffness;
+    }
+
+    pub fn get_viscosity(&self) -> f64 {
+        self.viscosity
+    }
+
+    pub fn set_viscosity(&mut self, viscosity: f64) {
+        self.viscosity = viscosity;
+    }
+
+    pub fn get_surface_tension(&self) -> f64 {
+        self.surface_tension
+    }
+
+    pub fn set_surface_tension(&mut self, surface_tension: f64) {
+        self.surface_tension = surface_tension;
+    }
+
+    /// The 2D poly6 kernel, taking the squared distance.
+    fn density_kernel(&self, distance2: f64) -> f64 {
+
+        let h2 = self.smoothing_radius * self.smoothing_radius;
+
+        if distance2 >= h2 {
+            return 0.;
+        }
+
+        4. / (PI * h2.powi(4)) * (h2 - distance2).powi(3)
+
+    }
+
+    /// The magnitude of the 2D spiky kernel's gradient, pointing towards the neighbour.
+    fn pressure_kernel_gradient(&self, distance: f64) -> f64 {
+
+        let h = self.smoothing_radius;
+
+        if distance >= h {
+            return 0.;
+        }
+
+        -30. / (PI * h.powi(5)) * (h - distance).powi(2)
+
+    }
+
+    /// The 2D viscosity kernel's laplacian.
+    fn viscosity_kernel_laplacian(&self, distance: f64) -> f64 {
+
+        let h = self.smoothing_radius;
+
+        if distance >= h {
+            return 0.;
+        }
+
+        40. / (PI * h.powi(5)) * (h - distance)
+
+    }
+
+}
+
+impl ForceGenerator for SphFluid {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, d_t: f64) {
+
+        let pairs: Vec<_> = world.query_index_pairs(self.smoothing_radius).into_iter()
+            .filter(|&(a, b)| world.get_objects()[a].is_fluid() && world.get_objects()[b].is_fluid())
+            .collect();
+
+        let objects = world.get_objects_mut_no_wake();
+        let self_density = self.density_kernel(0.);
+
+        let mut densities: Vec<f64> = objects.iter().map(|object| object.get_mass() * self_density).collect();
+
+        for &(a, b) in pairs.iter() {
+            let weight = self.density_kernel((objects[a].get_position() - objects[b].get_position()).magnitude2());
+            densities[a] += objects[b].get_mass() * weight;
+            densities[b] += objects[a].get_mass() * weight;
+        }
+
+        // Clamping at zero keeps the pressure from pulling particles into clumps at the surface.
+        let pressures: Vec<f64> = densities.iter().map(|density| (self.stiffness * (density - self.rest_density)).max(0.)).collect();
+        let mut forces = vec![Vector2::zero(); objects.len()];
+
+        for &(a, b) in pairs.iter() {
+
+            let offset = objects[b].get_position() - objects[a].get_position();
+            let distance = offset.magnitude();
+
+            if distance == 0. {
+                continue;
+            }
+
+            let direction = offset / distance;
+            let mass_a = objects[a].get_mass();
+            let mass_b = objects[b].get_mass();
+
+            let pressure = (pressures[a] + pressures[b]) / 2. * self.pressure_kernel_gradient(distance);
+            forces[a] += direction * (mass_a * mass_b * pressure / (densities[a] * densities[b]));
+            forces[b] -= direction * (mass_a * mass_b * pressure / (densities[a] * densities[b]));
+
+            let relative_velocity = (objects[b].get_velocity() - objects[a].get_velocity()) / d_t;
+            let viscosity = relative_velocity * (self.viscosity * mass_a * mass_b * self.viscosity_kernel_laplacian(distance) / (densities[a] * densities[b]));
+            forces[a] += viscosity;
+            forces[b] -= viscosity;
+
+            let cohesion = offset * (self.surface_tension * mass_a * mass_b * self.density_kernel(distance * distance));
+            forces[a] += cohesion;
+            forces[b] -= cohesion;
+
+        }
+
+        for (object, force) in objects.iter_mut().zip(forces) {
+            object.force(force);
+        }
+
+    }
+
+}
@@ -0,0 +1,277 @@
+use std::any::Any;
+use cgmath::{InnerSpace, Vector2};
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// Identifies a force generator added to a [`PhysicsWorld`].
+pub type ForceGeneratorId = usize;
+
+/// Something that adds forces to a world's objects once per update, just before they are integrated.
+///
+/// Generators run in the order they were added and see the whole world, so they can use its queries to find the
+/// objects they affect. Sleeping objects discard any force they are given. Once added, a generator can be reached
+/// again by its concrete type through [`PhysicsWorld::get_force_generator_mut`].
+pub trait ForceGenerator: Any {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, d_t: f64);
+
+}
+
+pub(crate) struct ForceGeneratorEntry {
+    pub(crate) id: ForceGeneratorId,
+    pub(crate) enabled: bool,
+    pub(crate) generator: Box<dyn ForceGenerator>,
+}
+
+/// Accelerates every object equally, regardless of its mass.
+pub struct UniformGravity {
+
+    acceleration: Vector2<f64>,
+
+}
+
+impl UniformGravity {
+
+    pub fn new(acceleration: Vector2<f64>) -> UniformGravity {
+        UniformGravity {
+            acceleration,
+        }
+    }
+
+    pub fn get_acceleration(&self) -> Vector2<f64> {
+        self.acceleration
+    }
+
+    pub fn set_acceleration(&mut self, acceleration: Vector2<f64>) {
+        self.acceleration = acceleration;
+    }
+
+}
+
+impl ForceGenerator for UniformGravity {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, _d_t: f64) {
+
+        for object in world.get_objects_mut_no_wake().iter_mut() {
+            let mass = object.get_mass();
+            object.force(self.acceleration * mass);
+        }
+
+    }
+
+}
+
+/// Pulls objects towards a point with an acceleration falling off with the square of their distance.
+/// A negative strength pushes them away instead.
+pub struct PointAttractor {
+
+    position: Vector2<f64>,
+    strength: f64,
+    softening: f64,
+    radius: Option<f64>,
+
+}
+
+impl PointAttractor {
+
+    /// `softening` is added to the distance so objects passing through `position` aren't flung away.
+    pub fn new(position: Vector2<f64>, strength: f64, softening: f64) -> PointAttractor {
+        PointAttractor {
+            position,
+            strength,
+            softening,
+            radius: None,
+        }
+    }
+
+    pub fn get_position(&self) -> Vector2<f64> {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vector2<f64>) {
+        self.position = position;
+    }
+
+    pub fn get_strength(&self) -> f64 {
+        self.strength
+    }
+
+    pub fn set_strength(&mut self, strength: f64) {
+        self.strength = strength;
+    }
+
+    /// Limits the attractor to objects within `radius` of it, or lets it reach everything with `None`.
+    pub fn set_radius(&mut self, radius: Option<f64>) {
+        self.radius = radius;
+    }
+
+    pub fn get_radius(&self) -> Option<f64> {
+        self.radius
+    }
+
+}
+
+impl ForceGenerator for PointAttractor {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, _d_t: f64) {
+
+        let indices: Vec<usize> = match self.radius {
+            Some(radius) => world.query_radius(self.position, radius).into_iter().filter_map(|handle| world.get_index(handle)).collect(),
+            None => (0..world.get_objects().len()).collect(),
+        };
+
+        let objects = world.get_objects_mut_no_wake();
+
+        for index in indices {
+
+            let object = &mut objects[index];
+            let offset = self.position - object.get_position();
+            let distance2 = offset.magnitude2() + self.softening * self.softening;
+
+            if distance2 == 0. {
+                continue;
+            }
+
+            let mass = object.get_mass();
+            object.force(offset * (self.strength * mass / (distance2 * distance2.sqrt())));
+
+        }
+
+    }
+
+}
+
+/// Slows objects down with a force `linear * v + quadratic * |v| * v` opposing their velocity.
+pub struct Drag {
+
+    linear: f64,
+    quadratic: f64,
+
+}
+
+impl Drag {
+
+    pub fn new(linear: f64, quadratic: f64) -> Drag {
+        Drag {
+            linear,
+            quadratic,
+        }
+    }
+
+    pub fn get_linear(&self) -> f64 {
+        self.linear
+    }
+
+    pub fn set_linear(&mut self, linear: f64) {
+        self.linear = linear;
+    }
+
+    pub fn get_quadratic(&self) -> f64 {
+        self.quadratic
+    }
+
+    pub fn set_quadratic(&mut self, quadratic: f64) {
+        self.quadratic = quadratic;
+    }
+
+}
+
+impl ForceGenerator for Drag {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, d_t: f64) {
+
+        for object in world.get_objects_mut_no_wake().iter_mut() {
+
+            let velocity = object.get_velocity() / d_t;
+            object.force(-velocity * (self.linear + self.quadratic * velocity.magnitude()));
+
+        }
+
+    }
+
+}
+
+/// A damped spring between one object and a fixed point in the world.
+pub struct AnchorSpring {
+
+    handle: ObjectHandle,
+    anchor: Vector2<f64>,
+    rest_length: f64,
+    stiffness: f64,
+    damping: f64,
+
+}
+
+impl AnchorSpring {
+
+    pub fn new(handle: ObjectHandle, anchor: Vector2<f64>, rest_length: f64, stiffness: f64, damping: f64) -> AnchorSpring {
+        AnchorSpring {
+            handle,
+            anchor,
+            rest_length,
+            stiffness,
+            damping,
+        }
+    }
+
+    pub fn get_handle(&self) -> ObjectHandle {
+        self.handle
+    }
+
+    pub fn get_anchor(&self) -> Vector2<f64> {
+        self.anchor
+    }
+
+    pub fn set_anchor(&mut self, anchor: Vector2<f64>) {
+        self.anchor = anchor;
+    }
+
+    pub fn get_rest_length(&self) -> f64 {
+        self.rest_length
+    }
+
+    pub fn set_rest_length(&mut self, rest_length: f64) {
+        self.rest_length = rest_length;
+    }
+
+    pub fn get_stiffness(&self) -> f64 {
+        self.stiffness
+    }
+
+    pub fn set_stiffness(&mut self, stiffness: f64) {
+        self.stiffness = stiffness;
+    }
+
+    pub fn get_damping(&self) -> f64 {
+        self.damping
+    }
+
+    pub fn set_damping(&mut self, damping: f64) {
+        self.damping = damping;
+    }
+
+}
+
+impl ForceGenerator for AnchorSpring {
+
+    fn apply(&mut self, world: &mut PhysicsWorld, d_t: f64) {
+
+        let object = match world.get_index(self.handle) {
+            Some(index) => &mut world.get_objects_mut_no_wake()[index],
+            None => return,
+        };
+
+        let offset = self.anchor - object.get_position();
+        let length = offset.magnitude();
+
+        if length == 0. {
+            return;
+        }
+
+        let direction = offset / length;
+        let stretch_speed = -(object.get_velocity() / d_t).dot(direction);
+
+        object.force(direction * (self.stiffness * (length - self.rest_length) + self.damping * stretch_speed));
+
+    }
+
+}
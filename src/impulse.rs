@@ -0,0 +1,70 @@
+use cgmath::{InnerSpace, Vector2};
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// How a radial impulse weakens between its centre and its edge.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Falloff {
+    Constant,
+    Linear,
+    Quadratic,
+}
+
+impl Falloff {
+
+    /// The strength multiplier at `fraction` of the way from the centre to the edge.
+    pub fn factor(&self, fraction: f64) -> f64 {
+
+        let remaining = (1. - fraction).clamp(0., 1.);
+
+        match self {
+            Falloff::Constant => 1.,
+            Falloff::Linear => remaining,
+            Falloff::Quadratic => remaining * remaining,
+        }
+
+    }
+
+}
+
+impl PhysicsWorld {
+
+    /// Changes an object's velocity by `impulse / mass` immediately. Returns false if the object doesn't exist.
+    ///
+    /// The velocity is implied by the previous position, so that is what moves, scaled by the time step of the
+    /// most recent update.
+    pub fn apply_impulse(&mut self, handle: ObjectHandle, impulse: Vector2<f64>) -> bool {
+
+        let time_step = self.get_time_step();
+
+        let object = match self.get_object_mut(handle) {
+            Some(object) => object,
+            None => return false,
+        };
+
+        let velocity_change = impulse / object.get_mass();
+        object.set_prev_position(object.get_prev_position() - velocity_change * time_step);
+
+        return true;
+
+    }
+
+    /// Pushes every object whose centre is within `radius` of `center` directly away from it, like an explosion.
+    /// A negative `strength` pulls them in instead.
+    pub fn apply_radial_impulse(&mut self, center: Vector2<f64>, radius: f64, strength: f64, falloff: Falloff) {
+
+        for handle in self.query_radius(center, radius) {
+
+            let offset = self.get_object(handle).unwrap().get_position() - center;
+            let distance = offset.magnitude();
+
+            if distance == 0. {
+                continue;
+            }
+
+            self.apply_impulse(handle, offset / distance * (strength * falloff.factor(distance / radius)));
+
+        }
+
+    }
+
+}
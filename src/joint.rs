@@ -0,0 +1,254 @@
+use cgmath::{InnerSpace, Vector2, Zero};
+use crate::circle::Circle;
+use crate::physics_object::ObjectHandle;
+use crate::rigid_body::{rotate, RigidBodyId};
+
+/// Identifies a joint added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type JointId = usize;
+
+/// One side of a joint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JointBody {
+    /// A fixed point in the world.
+    World,
+    /// A single object, which turns about its own centre.
+    Object(ObjectHandle),
+    Rigid(RigidBodyId),
+}
+
+/// Where one side of a joint is and how hard it is to move, found fresh each time the joint is solved.
+pub(crate) struct BodyFrame {
+
+    pub(crate) indices: Vec<usize>,
+    pub(crate) center: Vector2<f64>,
+    pub(crate) angle: f64,
+    pub(crate) inverse_mass: f64,
+    pub(crate) inverse_inertia: f64,
+
+}
+
+impl BodyFrame {
+
+    /// The frame of the world itself, which nothing can move.
+    pub(crate) fn world() -> BodyFrame {
+        BodyFrame {
+            indices: Vec::new(),
+            center: Vector2::zero(),
+            angle: 0.,
+            inverse_mass: 0.,
+            inverse_inertia: 0.,
+        }
+    }
+
+    /// The frame of the objects at `indices` moving as one, centred on `center` and turned to `angle`.
+    pub(crate) fn rigid(objects: &[Circle], indices: Vec<usize>, center: Vector2<f64>, angle: f64) -> BodyFrame {
+
+        // Kinematic objects can't be moved by a joint, and neither can anything rigidly attached to them.
+        if indices.iter().any(|&index| objects[index].is_kinematic()) {
+            return BodyFrame {
+                indices: Vec::new(),
+                center,
+                angle,
+                inverse_mass: 0.,
+                inverse_inertia: 0.,
+            };
+        }
+
+        let mut mass = 0.;
+        let mut inertia = 0.;
+
+        for &index in indices.iter() {
+            let object = &objects[index];
+            mass += object.get_mass();
+            inertia += object.get_inertia() + object.get_mass() * (object.get_position() - center).magnitude2();
+        }
+
+        BodyFrame {
+            indices,
+            center,
+            angle,
+            inverse_mass: 1. / mass,
+            inverse_inertia: 1. / inertia,
+        }
+
+    }
+
+    pub(crate) fn to_world(&self, local: Vector2<f64>) -> Vector2<f64> {
+        self.center + rotate(local, self.angle)
+    }
+
+    pub(crate) fn to_local(&self, point: Vector2<f64>) -> Vector2<f64> {
+        rotate(point - self.center, -self.angle)
+    }
+
+    /// How far the point at `arm` from the centre moves along `direction` per unit of impulse there.
+    fn inverse_mass_along(&self, arm: Vector2<f64>, direction: Vector2<f64>) -> f64 {
+
+        let turn = arm.x * direction.y - arm.y * direction.x;
+        self.inverse_mass + turn * turn * self.inverse_inertia
+
+    }
+
+    /// Applies a positional impulse at the point `arm` from the centre, moving and turning the body as a whole.
+    fn apply_impulse(&mut self, objects: &mut [Circle], arm: Vector2<f64>, impulse: Vector2<f64>) {
+
+        let translation = impulse * self.inverse_mass;
+        let rotation = (arm.x * impulse.y - arm.y * impulse.x) * self.inverse_inertia;
+        self.shift(objects, translation, rotation);
+
+    }
+
+    fn apply_angular_impulse(&mut self, objects: &mut [Circle], impulse: f64) {
+
+        let rotation = impulse * self.inverse_inertia;
+        self.shift(objects, Vector2::zero(), rotation);
+
+    }
+
+    fn shift(&mut self, objects: &mut [Circle], translation: Vector2<f64>, rotation: f64) {
+
+        if self.indices.is_empty() {
+            return;
+        }
+
+        for &index in self.indices.iter() {
+            let object = &mut objects[index];
+            object.set_raw_position(self.center + translation + rotate(object.get_position() - self.center, rotation));
+            object.set_raw_angle(object.get_angle() + rotation);
+        }
+
+        self.center += translation;
+        self.angle += rotation;
+
+    }
+
+}
+
+/// Turns a hinge's motor, driving the two sides to spin relative to each other at `speed` radians per second using no
+/// more than `max_torque`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Motor {
+
+    pub speed: f64,
+    pub max_torque: f64,
+
+}
+
+/// Pins a point of one body to a point of another, leaving them free to turn about it, optionally within limits or
+/// driven by a motor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HingeJoint {
+
+    a: JointBody,
+    b: JointBody,
+    local_anchor_a: Vector2<f64>,
+    local_anchor_b: Vector2<f64>,
+    reference_angle: f64,
+    limits: Option<(f64, f64)>,
+    motor: Option<Motor>,
+
+}
+
+impl HingeJoint {
+
+    /// Joins `a` and `b` at `anchor`, given in world coordinates and taken with the bodies where `frame_a` and
+    /// `frame_b` put them. Their relative angle at that moment counts as zero for the limits.
+    pub(crate) fn new(a: JointBody, b: JointBody, anchor: Vector2<f64>, frame_a: &BodyFrame, frame_b: &BodyFrame) -> HingeJoint {
+        HingeJoint {
+            a,
+            b,
+            local_anchor_a: frame_a.to_local(anchor),
+            local_anchor_b: frame_b.to_local(anchor),
+            reference_angle: frame_b.angle - frame_a.angle,
+            limits: None,
+            motor: None,
+        }
+    }
+
+    pub fn get_bodies(&self) -> (JointBody, JointBody) {
+        (self.a, self.b)
+    }
+
+    /// How far `b` has turned relative to `a` since the joint was made, given both frames.
+    pub(crate) fn angle_between(&self, frame_a: &BodyFrame, frame_b: &BodyFrame) -> f64 {
+        frame_b.angle - frame_a.angle - self.reference_angle
+    }
+
+    /// The lowest and highest relative angle, in radians, the joint allows. `None` lets it turn freely.
+    pub fn get_limits(&self) -> Option<(f64, f64)> {
+        self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: Option<(f64, f64)>) {
+        self.limits = limits;
+    }
+
+    pub fn get_motor(&self) -> Option<Motor> {
+        self.motor
+    }
+
+    pub fn set_motor(&mut self, motor: Option<Motor>) {
+        self.motor = motor;
+    }
+
+    /// Turns the two sides towards the motor's speed, if there is a motor.
+    pub(crate) fn drive(&self, objects: &mut [Circle], frame_a: &mut BodyFrame, frame_b: &mut BodyFrame, d_t: f64) {
+
+        let inverse_inertia = frame_a.inverse_inertia + frame_b.inverse_inertia;
+
+        if let (Some(motor), true) = (self.motor, inverse_inertia > 0.) {
+
+            let spin: f64 = frame_b.indices.first().map_or(0., |&index| objects[index].get_angular_velocity())
+                - frame_a.indices.first().map_or(0., |&index| objects[index].get_angular_velocity());
+            let limit = motor.max_torque * d_t * d_t;
+            let impulse = ((motor.speed * d_t - spin) / inverse_inertia).clamp(-limit, limit);
+
+            frame_a.apply_angular_impulse(objects, -impulse);
+            frame_b.apply_angular_impulse(objects, impulse);
+
+        }
+
+    }
+
+    /// Keeps the relative angle within the limits and pulls the two anchors back together, in that order so the pin
+    /// has the last word.
+    pub(crate) fn solve(&self, objects: &mut [Circle], frame_a: &mut BodyFrame, frame_b: &mut BodyFrame) {
+
+        let inverse_inertia = frame_a.inverse_inertia + frame_b.inverse_inertia;
+
+        if let (Some((lower, upper)), true) = (self.limits, inverse_inertia > 0.) {
+
+            let angle = self.angle_between(frame_a, frame_b);
+            let error = if angle < lower { lower - angle } else if angle > upper { upper - angle } else { 0. };
+
+            if error != 0. {
+                frame_a.apply_angular_impulse(objects, -error / inverse_inertia);
+                frame_b.apply_angular_impulse(objects, error / inverse_inertia);
+            }
+
+        }
+
+        let arm_a = rotate(self.local_anchor_a, frame_a.angle);
+        let arm_b = rotate(self.local_anchor_b, frame_b.angle);
+        let separation = (frame_b.center + arm_b) - (frame_a.center + arm_a);
+        let distance = separation.magnitude();
+
+        if distance == 0. {
+            return;
+        }
+
+        let direction = separation / distance;
+        let inverse_mass = frame_a.inverse_mass_along(arm_a, direction) + frame_b.inverse_mass_along(arm_b, direction);
+
+        if inverse_mass == 0. {
+            return;
+        }
+
+        let impulse = direction * (distance / inverse_mass);
+
+        frame_a.apply_impulse(objects, arm_a, impulse);
+        frame_b.apply_impulse(objects, arm_b, -impulse);
+
+    }
+
+}
@@ -0,0 +1,221 @@
+use std::f64::consts::PI;
+use cgmath::Vector2;
+use crate::physics_object::ObjectHandle;
+use crate::rigid_body::rotate;
+
+/// A placement in the world: a translation and a turn about it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pose {
+
+    pub position: Vector2<f64>,
+    pub angle: f64,
+
+}
+
+impl Pose {
+
+    pub fn new(position: Vector2<f64>, angle: f64) -> Pose {
+        Pose {
+            position,
+            angle,
+        }
+    }
+
+    /// Takes a point given relative to the pose into world coordinates.
+    pub fn apply(&self, local: Vector2<f64>) -> Vector2<f64> {
+        self.position + rotate(local, self.angle)
+    }
+
+    /// Takes a point in world coordinates to where it sits relative to the pose.
+    pub fn inverse_apply(&self, point: Vector2<f64>) -> Vector2<f64> {
+        rotate(point - self.position, -self.angle)
+    }
+
+    fn lerp(&self, other: &Pose, amount: f64) -> Pose {
+        Pose::new(self.position + (other.position - self.position) * amount, self.angle + (other.angle - self.angle) * amount)
+    }
+
+}
+
+/// How a keyframed motion moves between one keyframe and the next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Starts slow and speeds up.
+    EaseIn,
+    /// Starts fast and slows down.
+    EaseOut,
+    EaseInOut,
+    /// Holds the keyframe until the next one, then jumps.
+    Step,
+}
+
+impl Easing {
+
+    /// Maps the fraction of time between two keyframes to the fraction of the way between them.
+    pub fn apply(&self, fraction: f64) -> f64 {
+
+        match self {
+            Easing::Linear => fraction,
+            Easing::EaseIn => fraction * fraction,
+            Easing::EaseOut => fraction * (2. - fraction),
+            Easing::EaseInOut => (1. - (fraction * PI).cos()) / 2.,
+            Easing::Step => 0.,
+        }
+
+    }
+
+}
+
+/// A pose to be at at a given time, and how to get from it to the next keyframe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+
+    pub time: f64,
+    pub pose: Pose,
+    pub easing: Easing,
+
+}
+
+/// What a keyframed motion does once it reaches its last keyframe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Playback {
+    /// Stays at the last keyframe.
+    Once,
+    /// Jumps back to the first keyframe.
+    Loop,
+    /// Plays backwards to the first keyframe, then forwards again.
+    PingPong,
+}
+
+/// A path through time for a kinematic collider or object, sampled against the world's clock.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Motion {
+    /// Interpolates between keyframes sorted by time.
+    Keyframes { keyframes: Vec<Keyframe>, playback: Playback },
+    /// Circles `center` at `radius` without turning, `angular_speed` radians per second from `phase`.
+    Orbit { center: Vector2<f64>, radius: f64, angular_speed: f64, phase: f64 },
+    /// Turns about `pivot` at `angular_speed` radians per second from `phase`.
+    Spin { pivot: Vector2<f64>, angular_speed: f64, phase: f64 },
+}
+
+impl Motion {
+
+    pub fn sample(&self, time: f64) -> Pose {
+
+        match self {
+            Motion::Keyframes { keyframes, playback } => {
+
+                let (first, last) = match (keyframes.first(), keyframes.last()) {
+                    (Some(first), Some(last)) => (first, last),
+                    _ => return Pose::new(Vector2::new(0., 0.), 0.),
+                };
+
+                let duration = last.time - first.time;
+                let elapsed = time - first.time;
+
+                let local_time = if duration <= 0. {
+                    0.
+                } else {
+                    match playback {
+                        Playback::Once => elapsed.clamp(0., duration),
+                        Playback::Loop => elapsed.rem_euclid(duration),
+                        Playback::PingPong => {
+                            let phase = elapsed.rem_euclid(2. * duration);
+                            if phase > duration { 2. * duration - phase } else { phase }
+                        }
+                    }
+                } + first.time;
+
+                let next = keyframes.iter().position(|keyframe| keyframe.time > local_time).unwrap_or(keyframes.len() - 1);
+
+                if next == 0 {
+                    return first.pose;
+                }
+
+                let from = &keyframes[next - 1];
+                let to = &keyframes[next];
+
+                if to.time <= from.time {
+                    return to.pose;
+                }
+
+                let fraction = ((local_time - from.time) / (to.time - from.time)).clamp(0., 1.);
+                from.pose.lerp(&to.pose, from.easing.apply(fraction))
+
+            }
+            Motion::Orbit { center, radius, angular_speed, phase } => {
+
+                let angle = phase + angular_speed * time;
+                Pose::new(center + Vector2::new(angle.cos(), angle.sin()) * *radius, 0.)
+
+            }
+            Motion::Spin { pivot, angular_speed, phase } => Pose::new(*pivot, phase + angular_speed * time),
+        }
+
+    }
+
+}
+
+/// An object moved by a [`Motion`] rather than by forces and collisions.
+#[derive(Clone, Debug)]
+pub(crate) struct KinematicEntry {
+
+    pub(crate) handle: ObjectHandle,
+    pub(crate) motion: Motion,
+    pub(crate) local_position: Vector2<f64>,
+    pub(crate) local_angle: f64,
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn slide(playback: Playback) -> Motion {
+
+        let keyframe = |time: f64, x: f64| Keyframe { time, pose: Pose::new(Vector2::new(x, 0.), 0.), easing: Easing::Linear };
+        Motion::Keyframes { keyframes: vec![keyframe(0., 0.), keyframe(2., 10.)], playback }
+
+    }
+
+    fn x_at(motion: &Motion, time: f64) -> f64 {
+        motion.sample(time).position.x
+    }
+
+    #[test]
+    fn once_holds_the_ends() {
+
+        let motion = slide(Playback::Once);
+
+        assert!((x_at(&motion, 1.) - 5.).abs() < 1e-9);
+        assert!((x_at(&motion, 5.) - 10.).abs() < 1e-9);
+        assert!(x_at(&motion, -1.).abs() < 1e-9);
+
+    }
+
+    #[test]
+    fn loop_jumps_back_to_the_start() {
+
+        let motion = slide(Playback::Loop);
+
+        assert!((x_at(&motion, 2.5) - 2.5).abs() < 1e-9);
+        assert!((x_at(&motion, 7.) - 5.).abs() < 1e-9);
+        assert!((x_at(&motion, -0.5) - 7.5).abs() < 1e-9);
+
+    }
+
+    #[test]
+    fn ping_pong_plays_back_and_forth() {
+
+        let motion = slide(Playback::PingPong);
+
+        assert!((x_at(&motion, 2.5) - 7.5).abs() < 1e-9);
+        assert!((x_at(&motion, 3.5) - 2.5).abs() < 1e-9);
+        assert!((x_at(&motion, 4.5) - 2.5).abs() < 1e-9);
+        assert!((x_at(&motion, -0.5) - 2.5).abs() < 1e-9);
+
+    }
+
+}
@@ -2,20 +2,85 @@ use std::borrow::BorrowMut;
 use std::time::{Duration, Instant};
 use cgmath::{InnerSpace, MetricSpace, Vector2, Zero};
 use rand::{Rng, thread_rng};
-use sfml::graphics::{CircleShape, Color, Font, Rect, RectangleShape, RenderTarget, RenderWindow, Shape, Text, TextStyle, Transformable};
+use sfml::graphics::{CircleShape, Color, Font, PrimitiveType, Rect, RectangleShape, RenderStates, RenderTarget, RenderWindow, Shape, Text, TextStyle, Transformable, Vertex};
 use sfml::SfBox;
 use sfml::system::Vector2f;
-use sfml::window::{ContextSettings, Event, Style, VideoMode};
+use sfml::window::{ContextSettings, Event, Key, Style, VideoMode};
 use crate::physics_object::*;
 use crate::circle::*;
 use std::thread::*;
 use sfml::window::mouse::Button;
 use slider::Slider;
+use crate::force_generator::UniformGravity;
+use crate::impulse::Falloff;
+use crate::mouse_joint::MouseJoint;
+use crate::emitter::{Emitter, Region};
+use crate::scene::Scene;
 
 mod physics_object;
 mod circle;
 mod slider;
 
+// The engine offers more than the demo below uses, so unused parts of it are expected rather than dead.
+#[allow(dead_code)]
+mod query;
+#[allow(dead_code)]
+mod raycast;
+#[allow(dead_code)]
+mod collider;
+#[allow(dead_code)]
+mod ccd;
+#[allow(dead_code)]
+mod sleeping;
+#[allow(dead_code)]
+mod force_generator;
+#[allow(dead_code)]
+mod barnes_hut;
+#[allow(dead_code)]
+mod electrostatics;
+#[allow(dead_code)]
+mod species;
+#[allow(dead_code)]
+mod fluid;
+#[allow(dead_code)]
+mod material;
+#[allow(dead_code)]
+mod constraint;
+#[allow(dead_code)]
+mod cohesion;
+#[allow(dead_code)]
+mod thermal;
+#[allow(dead_code)]
+mod event;
+#[allow(dead_code)]
+mod accretion;
+#[allow(dead_code)]
+mod impulse;
+#[allow(dead_code)]
+mod noise;
+#[allow(dead_code)]
+mod flow_field;
+#[allow(dead_code)]
+mod buoyancy;
+#[allow(dead_code)]
+mod rigid_body;
+#[allow(dead_code)]
+mod soft_body;
+#[allow(dead_code)]
+mod balloon;
+#[allow(dead_code)]
+mod joint;
+#[allow(dead_code)]
+mod mouse_joint;
+#[allow(dead_code)]
+mod kinematic;
+#[allow(dead_code)]
+mod portal;
+#[allow(dead_code)]
+mod emitter;
+#[allow(dead_code)]
+mod scene;
+
 static time_steps: i32 = 8;
 
 fn main() {
@@ -28,13 +93,15 @@ fn main() {
     let mut window = RenderWindow::new((640, 640), "Physics", Style::default(), &Default::default());
 
     let mut physics_world = PhysicsWorld::new(width as u32, height as u32);
+    physics_world.add_boundary_walls();
+    physics_world.set_sleeping_enabled(true);
 
-    for index in 0..20000 {
+    for _ in 0..20000 {
 
-        physics_world.push_object(
+        let handle = physics_world.push_object(
             Circle::new(Vector2::new(thread_rng().gen_range(0.0..width), thread_rng().gen_range(0.0..height)),
                         1., 1.), CircleShape::new(1., 3));
-        physics_world.get_object_mut(index).unwrap().set_velocity(Vector2::new(rand::thread_rng().gen_range(-1.0..1.), 0.));
+        physics_world.get_object_mut(handle).unwrap().set_velocity(Vector2::new(rand::thread_rng().gen_range(-1.0..1.), 0.));
 
     }
 
@@ -42,15 +109,33 @@ fn main() {
     let mut middle_click_held = false;
     let mut left_click_held = false;
     let mut mouse_pos = (0., 0.);
-    let mut prev_mouse_pos = (0., 0.);
-    let mut particle_grabbed_index = 0;
+    let mut lasso: Vec<Vector2<f64>> = Vec::new();
+    let mut selection: Vec<ObjectHandle> = Vec::new();
+    let mut swept_while_held: Option<ObjectHandle> = None;
 
     let width_clone = width.clone();
     let height_clone = height.clone();
 
-    physics_world.set_update_predicate(Box::new(|object: &mut Circle| {
-        object.force(Vector2::new(0., 9.8 * 10. * object.get_mass()));
+    physics_world.add_force_generator(Box::new(UniformGravity::new(Vector2::new(0., 9.8 * 10.))));
+    physics_world.set_particle_budget(Some(30000));
 
+    // Right-clicking pours circles out of the cursor.
+    let mut cursor_emitter = Emitter::new(Region::Point(Vector2::new(0., 0.)), 60.);
+    cursor_emitter.set_enabled(false);
+    let cursor_emitter = physics_world.add_emitter(cursor_emitter);
+
+    if let Some(path) = std::env::args().nth(1) {
+        match Scene::load(&path) {
+            Ok(scene) => {
+                if let Err(error) = physics_world.add_scene(scene) {
+                    eprintln!("Couldn't add scene {}: {}", path, error);
+                }
+            }
+            Err(error) => eprintln!("Couldn't load scene {}: {}", path, error),
+        }
+    }
+
+    physics_world.set_update_predicate(Box::new(|object: &mut Circle| {
         let clamped_position = Vector2::new(
             object.get_position().x.clamp(0. + object.get_size(), 640. - object.get_size()),
             object.get_position().y.clamp(0. + object.get_size(), 640. - object.get_size()));
@@ -70,24 +155,35 @@ fn main() {
 
         let t = Instant::now();
 
+        if let Some(mouse_joint) = physics_world.get_mouse_joint_mut() {
+            mouse_joint.set_target(Vector2::new(mouse_pos.0, mouse_pos.1));
+        }
+
+        if let Some(emitter) = physics_world.get_emitter_mut(cursor_emitter) {
+            emitter.set_region(Region::Point(Vector2::new(mouse_pos.0, mouse_pos.1)));
+            emitter.set_enabled(right_click_held);
+        }
+
         for i in 0..time_steps {
 
             physics_world.update((1. / 60. / (time_steps as f64)));
 
-            if left_click_held {
+        }
 
-                let mut object = &mut physics_world.get_object_mut(particle_grabbed_index).unwrap();
+        // Nothing in the demo reacts to events yet, but they pile up until drained.
+        physics_world.drain_events();
 
-                object.set_raw_position(Vector2::new(mouse_pos.0, mouse_pos.1));
-                object.set_prev_position(Vector2::new(prev_mouse_pos.0, prev_mouse_pos.1));
+        physics_world.draw(&mut window);
 
+        if !lasso.is_empty() {
 
-            }
+            let vertices: Vec<Vertex> = lasso.iter()
+                .map(|point| Vertex::with_pos_color(Vector2f::new(point.x as f32, point.y as f32), Color::CYAN))
+                .collect();
+            window.draw_primitives(&vertices, PrimitiveType::LINE_STRIP, &RenderStates::default());
 
         }
 
-        physics_world.draw(&mut window);
-
         'event_loop : loop {
             match window.poll_event() {
                 Some(Event::Closed) => {
@@ -102,21 +198,59 @@ fn main() {
                         Button::LEFT => {
 
                             left_click_held = true;
-                            for object_index in 0..physics_world.get_objects().len() {
 
-                                let object = physics_world.get_object(object_index).unwrap();
+                            let mouse_pos_vector = Vector2::new(mouse_pos.0, mouse_pos.1);
+
+                            // Shift-dragging draws a lasso around the objects to drag next.
+                            if Key::LSHIFT.is_pressed() || Key::RSHIFT.is_pressed() {
 
-                                let mouse_pos_vector = Vector2::new(mouse_pos.0, mouse_pos.1);
+                                lasso = vec![mouse_pos_vector];
+                                break 'event_loop;
+
+                            }
+
+                            let mut mouse_joint = MouseJoint::new(mouse_pos_vector, 2000., 60., 5000.);
+
+                            // Clicking on any selected object drags the whole selection; clicking anywhere else drops it.
+                            let on_selection = selection.iter()
+                                .filter_map(|&handle| physics_world.get_object(handle))
+                                .any(|object| (mouse_pos_vector - object.get_position()).magnitude() < object.get_size() * 4.0);
+
+                            if !on_selection {
+                                selection.clear();
+                            }
+
+                            if on_selection {
+
+                                for &handle in selection.iter().filter(|&&handle| physics_world.contains_object(handle)) {
+                                    mouse_joint.grab(&physics_world, handle);
+                                }
+
+                            }
+                            else if let Some(&nearest) = physics_world.query_k_nearest(mouse_pos_vector, 1).first() {
+
+                                let object = physics_world.get_object(nearest).unwrap();
                                 let offset = mouse_pos_vector - object.get_position();
 
                                 if offset.magnitude() < object.get_size() * 4.0 {
 
-                                    particle_grabbed_index = object_index;
-                                    break 'event_loop;
+                                    mouse_joint.grab(&physics_world, nearest);
+
+                                    // Dragged objects move far enough per substep to tunnel without a sweep.
+                                    let object = physics_world.get_object_mut(nearest).unwrap();
+                                    if !object.is_ccd_enabled() {
+                                        object.set_ccd_enabled(true);
+                                        swept_while_held = Some(nearest);
+                                    }
+
                                 }
 
                             }
 
+                            if !mouse_joint.get_objects().is_empty() {
+                                physics_world.set_mouse_joint(Some(mouse_joint));
+                            }
+
                         }
                         Button::RIGHT => {
 
@@ -126,6 +260,7 @@ fn main() {
                         Button::MIDDLE => {
 
                             middle_click_held = true;
+                            physics_world.apply_radial_impulse(Vector2::new(mouse_pos.0, mouse_pos.1), 80., 200., Falloff::Linear);
 
                         }
                         _ => {
@@ -144,16 +279,36 @@ fn main() {
                             middle_click_held = false;
                         }
                         Button::LEFT => {
+
                             left_click_held = false;
+
+                            if !lasso.is_empty() {
+                                selection = physics_world.query_polygon(&lasso);
+                                lasso.clear();
+                            }
+
+                            physics_world.set_mouse_joint(None);
+
+                            if let Some(object) = swept_while_held.take().and_then(|handle| physics_world.get_object_mut(handle)) {
+                                object.set_ccd_enabled(false);
+                            }
+
                         }
                         _ => {
 
                         }
                     }
                 }
+                Some(Event::KeyPressed{ code, .. }) if code == Key::S => {
+                    // Shows which objects have gone to sleep.
+                    physics_world.set_debug_draw_sleeping(!physics_world.is_debug_draw_sleeping());
+                }
                 Some(Event::MouseMoved{ x, y}) => {
-                    prev_mouse_pos = mouse_pos;
                     mouse_pos = (x as f64, y as f64);
+
+                    if left_click_held && !lasso.is_empty() {
+                        lasso.push(Vector2::new(mouse_pos.0, mouse_pos.1));
+                    }
                 }
                 None => {break 'event_loop;}
                 _ => {}
@@ -161,14 +316,6 @@ fn main() {
             }
         }
 
-        if right_click_held {
-
-            physics_world.push_object(
-                Circle::new(Vector2::new(mouse_pos.0, mouse_pos.1),
-                            4., 1.), CircleShape::new(4.0, 16));
-
-        }
-
         std::thread::sleep(Duration::from_secs_f64((1. / 60. - t.elapsed().as_secs_f64()).max(0.)));
 
         let fps = 1. / t.elapsed().as_secs_f64();
@@ -0,0 +1,91 @@
+/// Surface properties shared by objects made of the same stuff.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+
+    stickiness: f64,
+    conductivity: f64,
+    heat_capacity: f64,
+    thermal_expansion: f64,
+    melting_point: f64,
+    friction: f64,
+
+}
+
+impl Material {
+
+    pub fn new() -> Material {
+        Material {
+            stickiness: 0.,
+            conductivity: 0.,
+            heat_capacity: 1.,
+            thermal_expansion: 0.,
+            melting_point: f64::INFINITY,
+            friction: 0.,
+        }
+    }
+
+    /// How readily touching objects bond, from 0 (never) to 1. It scales both the bond's stiffness and how far it can
+    /// stretch before breaking.
+    pub fn get_stickiness(&self) -> f64 {
+        self.stickiness
+    }
+
+    pub fn set_stickiness(&mut self, stickiness: f64) {
+        self.stickiness = stickiness;
+    }
+
+    /// How quickly heat flows through a contact with this material, in heat per second per degree of difference.
+    pub fn get_conductivity(&self) -> f64 {
+        self.conductivity
+    }
+
+    pub fn set_conductivity(&mut self, conductivity: f64) {
+        self.conductivity = conductivity;
+    }
+
+    /// The heat needed to warm one unit of mass by one degree.
+    pub fn get_heat_capacity(&self) -> f64 {
+        self.heat_capacity
+    }
+
+    pub fn set_heat_capacity(&mut self, heat_capacity: f64) {
+        self.heat_capacity = heat_capacity;
+    }
+
+    /// How much the radius grows, as a fraction of the rest radius, per degree above
+    /// [`REFERENCE_TEMPERATURE`](crate::thermal::REFERENCE_TEMPERATURE).
+    pub fn get_thermal_expansion(&self) -> f64 {
+        self.thermal_expansion
+    }
+
+    pub fn set_thermal_expansion(&mut self, thermal_expansion: f64) {
+        self.thermal_expansion = thermal_expansion;
+    }
+
+    /// The temperature at and above which the material loses its stickiness and any bonds it has break.
+    pub fn get_melting_point(&self) -> f64 {
+        self.melting_point
+    }
+
+    pub fn set_melting_point(&mut self, melting_point: f64) {
+        self.melting_point = melting_point;
+    }
+
+    /// The Coulomb friction coefficient of the surface. Contacts between two objects use the geometric mean of both
+    /// sides, so a frictionless material slides on anything. Static colliders have no material of their own, so
+    /// contacts with them use this coefficient alone.
+    pub fn get_friction(&self) -> f64 {
+        self.friction
+    }
+
+    pub fn set_friction(&mut self, friction: f64) {
+        self.friction = friction;
+    }
+
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::new()
+    }
+}
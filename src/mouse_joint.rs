@@ -0,0 +1,102 @@
+use cgmath::{InnerSpace, Vector2};
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// Pulls grabbed objects towards a target, usually the mouse cursor, with a damped spring.
+///
+/// Unlike moving the objects by hand the pull is an ordinary force, so grabbed objects still collide, drag along
+/// whatever they are joined to and can't be yanked harder than `max_force`. Each object is pulled towards the target
+/// plus the offset it was grabbed at, so a group keeps its layout while dragged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MouseJoint {
+
+    objects: Vec<(ObjectHandle, Vector2<f64>)>,
+    target: Vector2<f64>,
+    stiffness: f64,
+    damping: f64,
+    max_force: f64,
+
+}
+
+impl MouseJoint {
+
+    /// `stiffness` is the acceleration per unit of distance from the target and `damping` the deceleration per unit
+    /// of speed, both independent of mass so light and heavy objects follow alike.
+    pub fn new(target: Vector2<f64>, stiffness: f64, damping: f64, max_force: f64) -> MouseJoint {
+        MouseJoint {
+            objects: Vec::new(),
+            target,
+            stiffness,
+            damping,
+            max_force,
+        }
+    }
+
+    /// Grabs `handle` where it is now relative to the target.
+    pub fn grab(&mut self, world: &PhysicsWorld, handle: ObjectHandle) {
+
+        if let Some(object) = world.get_object(handle) {
+            self.objects.push((handle, object.get_position() - self.target));
+        }
+
+    }
+
+    pub fn get_objects(&self) -> &Vec<(ObjectHandle, Vector2<f64>)> {
+        &self.objects
+    }
+
+    pub fn get_target(&self) -> Vector2<f64> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Vector2<f64>) {
+        self.target = target;
+    }
+
+    pub fn get_stiffness(&self) -> f64 {
+        self.stiffness
+    }
+
+    pub fn set_stiffness(&mut self, stiffness: f64) {
+        self.stiffness = stiffness;
+    }
+
+    pub fn get_damping(&self) -> f64 {
+        self.damping
+    }
+
+    pub fn set_damping(&mut self, damping: f64) {
+        self.damping = damping;
+    }
+
+    pub fn get_max_force(&self) -> f64 {
+        self.max_force
+    }
+
+    pub fn set_max_force(&mut self, max_force: f64) {
+        self.max_force = max_force;
+    }
+
+    pub(crate) fn apply(&self, world: &mut PhysicsWorld, d_t: f64) {
+
+        for &(handle, offset) in self.objects.iter() {
+
+            let object = match world.get_object_mut(handle) {
+                Some(object) => object,
+                None => continue,
+            };
+
+            let error = self.target + offset - object.get_position();
+            let velocity = object.get_velocity() / d_t;
+            let mut force = (error * self.stiffness - velocity * self.damping) * object.get_mass();
+
+            if force.magnitude() > self.max_force {
+                force = force.normalize_to(self.max_force);
+            }
+
+            object.force(force);
+
+        }
+
+    }
+
+}
@@ -0,0 +1,81 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// Seeded 3D Perlin gradient noise.
+#[derive(Clone, Debug)]
+pub struct Perlin {
+
+    permutation: Vec<u8>,
+
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dots the offset with one of twelve gradients picked by the low bits of `hash`.
+fn gradient(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+
+}
+
+impl Perlin {
+
+    pub fn new(seed: u64) -> Perlin {
+
+        let mut permutation: Vec<u8> = (0..=255).collect();
+        permutation.shuffle(&mut StdRng::seed_from_u64(seed));
+
+        let doubled = permutation.iter().chain(permutation.iter()).cloned().collect();
+
+        Perlin {
+            permutation: doubled,
+        }
+
+    }
+
+    /// Samples the noise, giving a value roughly in -1..1 that varies smoothly with every coordinate.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+
+        let p = &self.permutation;
+
+        let xi = (x.floor() as i64 & 255) as usize;
+        let yi = (y.floor() as i64 & 255) as usize;
+        let zi = (z.floor() as i64 & 255) as usize;
+
+        let x = x - x.floor();
+        let y = y - y.floor();
+        let z = z - z.floor();
+
+        let u = fade(x);
+        let v = fade(y);
+        let w = fade(z);
+
+        let a = p[xi] as usize + yi;
+        let aa = p[a] as usize + zi;
+        let ab = p[a + 1] as usize + zi;
+        let b = p[xi + 1] as usize + yi;
+        let ba = p[b] as usize + zi;
+        let bb = p[b + 1] as usize + zi;
+
+        lerp(w,
+             lerp(v,
+                  lerp(u, gradient(p[aa], x, y, z), gradient(p[ba], x - 1., y, z)),
+                  lerp(u, gradient(p[ab], x, y - 1., z), gradient(p[bb], x - 1., y - 1., z))),
+             lerp(v,
+                  lerp(u, gradient(p[aa + 1], x, y, z - 1.), gradient(p[ba + 1], x - 1., y, z - 1.)),
+                  lerp(u, gradient(p[ab + 1], x, y - 1., z - 1.), gradient(p[bb + 1], x - 1., y - 1., z - 1.))))
+
+    }
+
+}
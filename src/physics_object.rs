@@ -1,23 +1,126 @@
+use std::any::Any;
 use std::cmp::Ordering;
+use rand::thread_rng;
 use std::ops::DerefMut;
 use std::sync::mpsc;
 use cgmath::{MetricSpace, Vector2};
-use sfml::graphics::{CircleShape, Color, RectangleShape, RenderTarget, RenderWindow, Shape, Transformable};
+use sfml::graphics::{CircleShape, Color, PrimitiveType, RectangleShape, RenderStates, RenderTarget, RenderWindow, Shape, Transformable, Vertex};
 use sfml::window::Event::Resized;
 use sfml::window::Window;
 use crate::{Circle, Zero};
+use crate::collider::{ColliderShape, StaticCollider, StaticColliderId};
+use crate::force_generator::{ForceGenerator, ForceGeneratorEntry, ForceGeneratorId};
+use crate::species::{species_color, InteractionMatrix};
+use crate::constraint::{ConstraintId, DistanceConstraint, DistanceConstraintEntry};
+use std::collections::HashSet;
+use crate::thermal::{conduct_heat_from_wall, HeatSource, HeatSourceId};
+use crate::event::PhysicsEvent;
+use crate::rigid_body::{RigidBody, RigidBodyId};
+use crate::soft_body::{SoftBody, SoftBodyId};
+use crate::balloon::{Balloon, BalloonId};
+use crate::joint::{BodyFrame, HingeJoint, JointBody, JointId};
+use crate::mouse_joint::MouseJoint;
+use crate::kinematic::{KinematicEntry, Motion};
+use crate::portal::{teleport_through_portals, PortalId, PortalPair};
+use crate::emitter::{Emitter, EmitterId, Sink, SinkId};
+use cgmath::InnerSpace;
 use std::thread::*;
 use cgmath::num_traits::FloatConst;
 use sfml::system::Vector2f;
 
+/// How many times the joints are solved each update, so chains of them settle.
+const JOINT_ITERATIONS: usize = 4;
+
 const GRID_SIZE: usize = 320;
 
+/// Identifies an object in a [`PhysicsWorld`].
+///
+/// Unlike an index into [`PhysicsWorld::get_objects`], a handle keeps referring to the same object while others are
+/// added and removed, and never refers to anything else once its object is gone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectHandle {
+    slot: u32,
+    generation: u32,
+}
+
+/// Where the object for each handle slot currently lives in the world's object list.
+struct HandleSlot {
+    index: Option<usize>,
+    generation: u32,
+}
+
+fn resolve_handle(slots: &[HandleSlot], handle: ObjectHandle) -> Option<usize> {
+
+    match slots.get(handle.slot as usize) {
+        Some(slot) if slot.generation == handle.generation => slot.index,
+        _ => None,
+    }
+
+}
+
+/// Borrows two different objects mutably at once.
+pub(crate) fn get_pair_mut(objects: &mut [Circle], a: usize, b: usize) -> (&mut Circle, &mut Circle) {
+
+    if a < b {
+        let (head, tail) = objects.split_at_mut(b);
+        (&mut head[a], &mut tail[0])
+    } else {
+        let (head, tail) = objects.split_at_mut(a);
+        (&mut tail[0], &mut head[b])
+    }
+
+}
+
 pub struct PhysicsWorld {
 
     objects : Vec<Circle>,
+    object_handles : Vec<ObjectHandle>,
+    handle_slots : Vec<HandleSlot>,
+    free_handle_slots : Vec<u32>,
     size : (u32, u32),
+    time_step : f64,
     grid : Vec<Vec<usize>>,
+    max_object_size : f64,
+    static_colliders : Vec<StaticCollider>,
+    sleeping_enabled : bool,
+    sleep_velocity_threshold : f64,
+    time_to_sleep : f64,
+    debug_draw_sleeping : bool,
+    debug_draw_rotation : bool,
+    distance_constraints : Vec<DistanceConstraintEntry>,
+    next_constraint_id : ConstraintId,
+    rigid_bodies : Vec<(RigidBodyId, RigidBody)>,
+    next_rigid_body_id : RigidBodyId,
+    next_collision_group : u32,
+    soft_bodies : Vec<(SoftBodyId, SoftBody)>,
+    next_soft_body_id : SoftBodyId,
+    balloons : Vec<(BalloonId, Balloon)>,
+    next_balloon_id : BalloonId,
+    joints : Vec<(JointId, HingeJoint)>,
+    next_joint_id : JointId,
+    mouse_joint : Option<MouseJoint>,
+    time : f64,
+    kinematic_objects : Vec<KinematicEntry>,
+    portal_pairs : Vec<(PortalId, PortalPair)>,
+    next_portal_id : PortalId,
+    emitters : Vec<(EmitterId, Emitter)>,
+    next_emitter_id : EmitterId,
+    sinks : Vec<(SinkId, Sink)>,
+    next_sink_id : SinkId,
+    particle_budget : Option<usize>,
+    cohesion_enabled : bool,
+    bond_speed_threshold : f64,
+    bond_breaking_strain : f64,
+    debug_draw_bonds : bool,
+    thermal_enabled : bool,
+    heat_sources : Vec<(HeatSourceId, HeatSource)>,
+    next_heat_source_id : HeatSourceId,
     update_predicate: Box<dyn Fn(&mut Circle)>,
+    force_generators: Vec<ForceGeneratorEntry>,
+    next_force_generator_id: ForceGeneratorId,
+    interaction_matrix: Option<InteractionMatrix>,
+    merge_speed_threshold: Option<f64>,
+    events: Vec<PhysicsEvent>,
     draw_predicate: Box<dyn Fn(&Circle, &mut CircleShape)>,
     circle_shapes: Vec<CircleShape<'static>>,
 
@@ -30,10 +133,54 @@ impl PhysicsWorld {
 
         let mut new_world = PhysicsWorld {
             objects: Vec::new(),
+            object_handles: Vec::new(),
+            handle_slots: Vec::new(),
+            free_handle_slots: Vec::new(),
             size: (width, height),
+            time_step: 1. / 60.,
             grid: Vec::new(),
+            max_object_size: 0.,
+            static_colliders: Vec::new(),
+            sleeping_enabled: false,
+            sleep_velocity_threshold: 5.,
+            time_to_sleep: 0.5,
+            debug_draw_sleeping: false,
+            debug_draw_rotation: false,
+            distance_constraints: Vec::new(),
+            next_constraint_id: 0,
+            rigid_bodies: Vec::new(),
+            next_rigid_body_id: 0,
+            next_collision_group: 0,
+            soft_bodies: Vec::new(),
+            next_soft_body_id: 0,
+            balloons: Vec::new(),
+            next_balloon_id: 0,
+            joints: Vec::new(),
+            next_joint_id: 0,
+            mouse_joint: None,
+            time: 0.,
+            kinematic_objects: Vec::new(),
+            portal_pairs: Vec::new(),
+            next_portal_id: 0,
+            emitters: Vec::new(),
+            next_emitter_id: 0,
+            sinks: Vec::new(),
+            next_sink_id: 0,
+            particle_budget: None,
+            cohesion_enabled: false,
+            bond_speed_threshold: 10.,
+            bond_breaking_strain: 0.5,
+            debug_draw_bonds: false,
+            thermal_enabled: false,
+            heat_sources: Vec::new(),
+            next_heat_source_id: 0,
             circle_shapes: Vec::new(),
             update_predicate: Box::new(|_| {}),
+            force_generators: Vec::new(),
+            next_force_generator_id: 0,
+            interaction_matrix: None,
+            merge_speed_threshold: None,
+            events: Vec::new(),
             draw_predicate: Box::new(|_, _| {}),
         };
 
@@ -45,8 +192,131 @@ impl PhysicsWorld {
 
     }
 
+    /// Sets a closure run on every object right after it is integrated, for per-object constraints such as
+    /// keeping it inside the window. Forces belong in a [`ForceGenerator`] instead.
     pub fn set_update_predicate(&mut self, predicate: Box<dyn Fn(&mut Circle)>) {
         self.update_predicate = predicate;
+        self.wake_all();
+    }
+
+    /// Appends a generator to the end of the pipeline run before integration each update.
+    pub fn add_force_generator(&mut self, generator: Box<dyn ForceGenerator>) -> ForceGeneratorId {
+
+        let position = self.force_generators.len();
+        self.insert_force_generator(position, generator)
+
+    }
+
+    /// Inserts a generator so it runs at `position` in the pipeline, shifting later ones back.
+    pub fn insert_force_generator(&mut self, position: usize, generator: Box<dyn ForceGenerator>) -> ForceGeneratorId {
+
+        let id = self.next_force_generator_id;
+        self.next_force_generator_id += 1;
+
+        self.force_generators.insert(position.min(self.force_generators.len()), ForceGeneratorEntry {
+            id,
+            enabled: true,
+            generator,
+        });
+        self.wake_all();
+
+        return id;
+
+    }
+
+    pub fn remove_force_generator(&mut self, id: ForceGeneratorId) -> Option<Box<dyn ForceGenerator>> {
+
+        let position = self.force_generators.iter().position(|entry| entry.id == id)?;
+        self.wake_all();
+
+        Some(self.force_generators.remove(position).generator)
+
+    }
+
+    /// The generator with `id`, if it is a `T`.
+    pub fn get_force_generator<T: ForceGenerator>(&self, id: ForceGeneratorId) -> Option<&T> {
+
+        let entry = self.force_generators.iter().find(|entry| entry.id == id)?;
+        (entry.generator.as_ref() as &dyn Any).downcast_ref::<T>()
+
+    }
+
+    /// The generator with `id`, if it is a `T`, for changing its settings after it has been added. Wakes everything,
+    /// as adding or removing a generator does, since the forces may be about to change.
+    pub fn get_force_generator_mut<T: ForceGenerator>(&mut self, id: ForceGeneratorId) -> Option<&mut T> {
+
+        self.wake_all();
+
+        let entry = self.force_generators.iter_mut().find(|entry| entry.id == id)?;
+        (entry.generator.as_mut() as &mut dyn Any).downcast_mut::<T>()
+
+    }
+
+    pub fn is_force_generator_enabled(&self, id: ForceGeneratorId) -> Option<bool> {
+
+        self.force_generators.iter().find(|entry| entry.id == id).map(|entry| entry.enabled)
+
+    }
+
+    /// Turns a generator on or off without changing its place in the pipeline. Returns false if there is no such generator.
+    pub fn set_force_generator_enabled(&mut self, id: ForceGeneratorId, enabled: bool) -> bool {
+
+        let entry = match self.force_generators.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        if entry.enabled != enabled {
+            entry.enabled = enabled;
+            self.wake_all();
+        }
+
+        return true;
+
+    }
+
+    /// The ids of every generator, in the order they run.
+    pub fn get_force_generator_ids(&self) -> Vec<ForceGeneratorId> {
+
+        self.force_generators.iter().map(|entry| entry.id).collect()
+
+    }
+
+    pub fn get_interaction_matrix(&self) -> Option<&InteractionMatrix> {
+
+        self.interaction_matrix.as_ref()
+
+    }
+
+    pub fn get_interaction_matrix_mut(&mut self) -> Option<&mut InteractionMatrix> {
+
+        self.wake_all();
+        self.interaction_matrix.as_mut()
+
+    }
+
+    /// Sets the species interactions applied between nearby objects every update, or turns them off with `None`.
+    pub fn set_interaction_matrix(&mut self, interaction_matrix: Option<InteractionMatrix>) {
+
+        self.interaction_matrix = interaction_matrix;
+        self.wake_all();
+
+    }
+
+    fn apply_force_generators(&mut self, d_t: f64) {
+
+        // Generators get the whole world, so take them out of it while they run.
+        let mut force_generators = std::mem::take(&mut self.force_generators);
+
+        for entry in force_generators.iter_mut() {
+            if entry.enabled {
+                entry.generator.apply(self, d_t);
+            }
+        }
+
+        force_generators.append(&mut self.force_generators);
+        self.force_generators = force_generators;
+
     }
 
     pub fn set_draw_predicate(&mut self, predicate: Box<dyn Fn(&Circle, &mut CircleShape)>) {
@@ -55,144 +325,1815 @@ impl PhysicsWorld {
 
     fn convert_to_grid_index(&self, x: f64, y: f64) -> usize {
 
+        let (x_index, y_index) = self.convert_to_grid_coordinates(x, y);
+
+        return x_index + y_index * GRID_SIZE;
+
+    }
+
+    /// Returns the column and row of the grid cell containing `(x, y)`, clamped to the grid.
+    pub(crate) fn convert_to_grid_coordinates(&self, x: f64, y: f64) -> (usize, usize) {
+
         let x_index = (x / self.size.0 as f64 * GRID_SIZE as f64).min((GRID_SIZE - 1) as f64) as usize;
         let y_index = (y / self.size.1 as f64 * GRID_SIZE as f64).min((GRID_SIZE - 1) as f64) as usize;
 
-        return x_index + y_index * GRID_SIZE;
+        return (x_index, y_index);
 
     }
 
-    pub fn update (&mut self, d_t: f64) {
+    /// The objects whose centres were in the given grid cell when the grid was last rebuilt.
+    pub(crate) fn get_grid_cell(&self, x_index: usize, y_index: usize) -> &Vec<usize> {
 
-        for object_index in 0..self.objects.len() as usize {
-            let object = &mut self.objects[object_index];
-            let object_position = object.get_position();
-            let grid_index = self.convert_to_grid_index(object_position.x, object_position.y);
+        &self.grid[x_index + y_index * GRID_SIZE]
 
-            for grid_x_offset in 0..3 {
-                for grid_y_offset in 0..3 {
+    }
 
-                    let offset_index : usize = (grid_index as i32 + (grid_x_offset as i32 - 1) + (grid_y_offset as i32 - 1) * GRID_SIZE as i32) as usize;
+    pub(crate) fn get_grid_dimensions(&self) -> (usize, usize) {
 
-                    if offset_index >= GRID_SIZE * GRID_SIZE {
-                        continue;
-                    }
+        (GRID_SIZE, GRID_SIZE)
 
-                    for other_object_index_index in 0..self.grid[offset_index].len() {
-                        let other_object_index = *self.grid[offset_index].get(other_object_index_index).unwrap();
+    }
 
-                        if other_object_index == object_index {
-                            continue;
-                        }
+    pub(crate) fn get_cell_size(&self) -> Vector2<f64> {
 
-                        let other_object;
-                        let object_to_check;
-
-                        if other_object_index > object_index {
-                            let (head, tail) = self.objects.split_at_mut(other_object_index);
-                            other_object = &mut tail[0];
-                            object_to_check = &mut head[object_index];
-                        } else {
-                            let (head, tail) = self.objects.split_at_mut(object_index);
-                            other_object = &mut head[other_object_index];
-                            object_to_check = &mut tail[0];
-                        }
+        Vector2::new(self.size.0 as f64 / GRID_SIZE as f64, self.size.1 as f64 / GRID_SIZE as f64)
 
-                        object_to_check.resolve_collision(other_object);
+    }
 
-                    }
-                }
-            }
+    /// The largest radius of any object pushed so far, used to widen broadphase queries.
+    pub(crate) fn get_max_object_size(&self) -> f64 {
 
-        }
+        self.max_object_size
 
+    }
 
-        //self.objects.sort_by(|a, b| {
-        //    if a.get_position().y < b.get_position().y {
-        //        return Ordering::Less;
-        //    } else if a.get_position().y > b.get_position().y {
-        //        return Ordering::Greater;
-        //    } else {
-        //        if a.get_position().x < b.get_position().x {
-        //            return Ordering::Less;
-        //        } else if a.get_position().x > b.get_position().x {
-        //            return Ordering::Greater;
-        //        } else {
-        //            return Ordering::Equal;
-        //        }
-        //    }
-        //});
+    pub(crate) fn grow_max_object_size(&mut self, size: f64) {
 
-        for index in 0..GRID_SIZE * GRID_SIZE {
-            self.grid[index].clear();
+        self.max_object_size = self.max_object_size.max(size);
+
+    }
+
+    pub fn get_size(&self) -> (u32, u32) {
+
+        self.size
+
+    }
+
+    /// The `d_t` passed to the most recent update.
+    pub fn get_time_step(&self) -> f64 {
+
+        self.time_step
+
+    }
+
+    pub fn is_sleeping_enabled(&self) -> bool {
+
+        self.sleeping_enabled
+
+    }
+
+    /// Lets resting islands of touching objects fall asleep, skipping them in integration and collision resolution.
+    pub fn set_sleeping_enabled(&mut self, sleeping_enabled: bool) {
+
+        self.sleeping_enabled = sleeping_enabled;
+
+        if !sleeping_enabled {
+            self.wake_all();
         }
 
-        for object_index in 0..self.objects.len() as usize {
+    }
 
-            let object = &mut self.objects[object_index];
-            object.update(d_t);
-            (self.update_predicate)(object);
-            let grid_index = self.convert_to_grid_index(self.objects[object_index].get_position().x, self.objects[object_index].get_position().y);
-            self.grid[grid_index].push(object_index);
+    /// The speed, in units per second, below which an object counts as resting.
+    pub fn get_sleep_velocity_threshold(&self) -> f64 {
+
+        self.sleep_velocity_threshold
+
+    }
+
+    pub fn set_sleep_velocity_threshold(&mut self, sleep_velocity_threshold: f64) {
+
+        self.sleep_velocity_threshold = sleep_velocity_threshold;
+
+    }
+
+    /// How many seconds every object in an island must rest before the island falls asleep.
+    pub fn get_time_to_sleep(&self) -> f64 {
+
+        self.time_to_sleep
+
+    }
+
+    pub fn set_time_to_sleep(&mut self, time_to_sleep: f64) {
+
+        self.time_to_sleep = time_to_sleep;
+
+    }
+
+    pub fn is_debug_draw_sleeping(&self) -> bool {
+
+        self.debug_draw_sleeping
+
+    }
+
+    /// Draws sleeping objects in a flat colour instead of whatever the draw predicate chose.
+    pub fn set_debug_draw_sleeping(&mut self, debug_draw_sleeping: bool) {
+
+        self.debug_draw_sleeping = debug_draw_sleeping;
+
+    }
+
+    /// Draws a line from each object's centre to its rim along its angle, so spin is visible.
+    pub fn set_debug_draw_rotation(&mut self, debug_draw_rotation: bool) {
+
+        self.debug_draw_rotation = debug_draw_rotation;
+
+    }
+
+    pub fn wake_object(&mut self, handle: ObjectHandle) {
+
+        if let Some(index) = self.get_index(handle) {
+            self.objects[index].wake();
         }
 
     }
 
-    pub fn draw(&mut self, window : &mut RenderWindow){
+    pub fn wake_all(&mut self) {
 
-        for object_index in 0..self.circle_shapes.len() {
-            (self.draw_predicate)(&self.objects[object_index], &mut self.circle_shapes[object_index]);
-            window.draw(&self.circle_shapes[object_index]);
+        for object in self.objects.iter_mut() {
+            object.wake();
         }
 
-        // Visualize the grid
-        /*for x in 0..GRID_SIZE {
-            for y in 0..GRID_SIZE {
-                let mut rect = RectangleShape::new();
-                rect.set_size(Vector2f::new((self.size.0 as f32 / GRID_SIZE as f32), (self.size.1 as f32 / GRID_SIZE as f32)));
-                rect.set_position(Vector2f::new(x as f32 * (self.size.0 as f32 / GRID_SIZE as f32), y as f32 * (self.size.1 as f32 / GRID_SIZE as f32)));
-                let grid_particle_count = self.grid[x + y * GRID_SIZE].len();
-                rect.set_fill_color(Color::rgba((x as f64 / GRID_SIZE as f64 * 255.) as u8, (y as f64 / GRID_SIZE as f64 * 255.) as u8, (grid_particle_count as f64 * 2  as f64 * 255.) as u8, 50));
-                rect.set_outline_thickness(1.0);
-                rect.set_outline_color(Color::rgb(50, 50, 50));
-                window.draw(&rect);
-            }
-        }*/
+    }
+
+    pub fn add_distance_constraint(&mut self, constraint: DistanceConstraint) -> ConstraintId {
+
+        self.push_distance_constraint(constraint, false)
 
     }
 
-    pub fn get_objects(&self) -> &Vec<Circle> {
+    pub(crate) fn add_bond(&mut self, bond: DistanceConstraint) -> ConstraintId {
 
-        return &self.objects;
+        self.push_distance_constraint(bond, true)
 
     }
 
-    pub fn get_objects_mut(&mut self) -> &mut Vec<Circle> {
+    fn push_distance_constraint(&mut self, constraint: DistanceConstraint, bond: bool) -> ConstraintId {
 
-        &mut self.objects
+        let id = self.next_constraint_id;
+        self.next_constraint_id += 1;
+
+        let (a, b) = constraint.get_objects();
+        self.wake_object(a);
+        self.wake_object(b);
+
+        self.distance_constraints.push(DistanceConstraintEntry {
+            id,
+            constraint,
+            bond,
+        });
+
+        return id;
 
     }
 
-    pub fn get_object(&self, index : usize) -> Option<&Circle> {
+    pub fn remove_distance_constraint(&mut self, id: ConstraintId) -> Option<DistanceConstraint> {
+
+        let position = self.distance_constraints.iter().position(|entry| entry.id == id)?;
+        let entry = self.distance_constraints.remove(position);
 
-        self.objects.get(index)
+        let (a, b) = entry.constraint.get_objects();
+        self.wake_object(a);
+        self.wake_object(b);
+
+        Some(entry.constraint)
 
     }
 
-    pub fn get_object_mut(&mut self, index : usize) -> Option<&mut Circle> {
+    pub fn get_distance_constraint(&self, id: ConstraintId) -> Option<&DistanceConstraint> {
 
-        self.objects.get_mut(index)
+        self.distance_constraints.iter().find(|entry| entry.id == id).map(|entry| &entry.constraint)
 
     }
 
-    pub fn push_object(&mut self, circle: Circle, circle_shape: CircleShape<'static>) {
+    pub fn get_distance_constraint_mut(&mut self, id: ConstraintId) -> Option<&mut DistanceConstraint> {
 
-        let grid_index = self.convert_to_grid_index(circle.get_position().x, circle.get_position().y);
-        self.grid[grid_index].push(self.objects.len());
-        self.objects.push(circle);
-        self.circle_shapes.push(circle_shape);
+        self.distance_constraints.iter_mut().find(|entry| entry.id == id).map(|entry| &mut entry.constraint)
 
     }
 
-}
+    /// The ids of every distance constraint, including bonds made by cohesion.
+    pub fn get_distance_constraint_ids(&self) -> Vec<ConstraintId> {
+
+        self.distance_constraints.iter().map(|entry| entry.id).collect()
+
+    }
+
+    pub(crate) fn get_bonded_pairs(&self) -> HashSet<(ObjectHandle, ObjectHandle)> {
+
+        self.distance_constraints.iter()
+            .filter(|entry| entry.bond)
+            .map(|entry| entry.constraint.get_objects())
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect()
+
+    }
+
+    /// Objects that belong to a rigid body, soft body, balloon or joint, which would break if the object went away.
+    pub(crate) fn get_structure_members(&self) -> HashSet<ObjectHandle> {
+
+        let mut members: HashSet<ObjectHandle> = HashSet::new();
+
+        members.extend(self.rigid_bodies.iter().flat_map(|(_, body)| body.get_members().iter().copied()));
+        members.extend(self.soft_bodies.iter().flat_map(|(_, body)| body.get_members().iter().copied()));
+        members.extend(self.balloons.iter().flat_map(|(_, balloon)| balloon.get_members().iter().copied()));
+
+        for (_, joint) in self.joints.iter() {
+            let (a, b) = joint.get_bodies();
+            for body in [a, b] {
+                if let JointBody::Object(handle) = body {
+                    members.insert(handle);
+                }
+            }
+        }
+
+        return members;
+
+    }
+
+    /// Solves every distance constraint once, removing any strained past breaking, and returns the pairs still held
+    /// together so they can share an island.
+    fn solve_distance_constraints(&mut self) -> Vec<(usize, usize)> {
+
+        let objects = &mut self.objects;
+        let slots = &self.handle_slots;
+        let mut linked = Vec::new();
+
+        self.distance_constraints.retain(|entry| {
+
+            let (handle_a, handle_b) = entry.constraint.get_objects();
+
+            let (index_a, index_b) = match (resolve_handle(slots, handle_a), resolve_handle(slots, handle_b)) {
+                (Some(index_a), Some(index_b)) if index_a != index_b => (index_a, index_b),
+                _ => return false,
+            };
+
+            let (a, b) = get_pair_mut(objects, index_a, index_b);
+            let melted = entry.bond && (a.is_melted() || b.is_melted());
+
+            if let Some(breaking_strain) = entry.constraint.get_breaking_strain() {
+                if melted || entry.constraint.strain_between(a, b) > breaking_strain {
+                    a.wake();
+                    b.wake();
+                    return false;
+                }
+            }
+
+            match (a.is_sleeping(), b.is_sleeping()) {
+                (true, true) => {}
+                (true, false) => a.wake(),
+                (false, true) => b.wake(),
+                (false, false) => {}
+            }
+
+            if !a.is_sleeping() {
+                entry.constraint.solve_between(a, b);
+            }
+
+            linked.push((index_a, index_b));
+
+            return true;
+
+        });
+
+        return linked;
+
+    }
+
+    /// Hands out a collision group no other object or body is using yet.
+    pub fn new_collision_group(&mut self) -> u32 {
+
+        let group = self.next_collision_group;
+        self.next_collision_group += 1;
+
+        return group;
+
+    }
+
+    /// Whether the object already belongs to a rigid or soft body.
+    fn is_body_member(&self, handle: ObjectHandle) -> bool {
+
+        self.rigid_bodies.iter().any(|(_, body)| body.get_members().contains(&handle))
+            || self.soft_bodies.iter().any(|(_, body)| body.get_members().contains(&handle))
+
+    }
+
+    /// Glues `members` into one [`RigidBody`]. Returns `None` if any of them is missing or already part of a body.
+    pub fn add_rigid_body(&mut self, members: &[ObjectHandle]) -> Option<RigidBodyId> {
+
+        if members.is_empty() {
+            return None;
+        }
 
+        let mut indices = Vec::new();
+
+        for &handle in members {
+
+            match self.get_index(handle) {
+                Some(index) if !self.is_body_member(handle) && !indices.contains(&index) => indices.push(index),
+                _ => return None,
+            }
+
+        }
+
+        let collision_group = self.new_collision_group();
+
+        for &index in indices.iter() {
+            self.objects[index].set_collision_group(Some(collision_group));
+            self.objects[index].wake();
+        }
+
+        let id = self.next_rigid_body_id;
+        self.next_rigid_body_id += 1;
+        self.rigid_bodies.push((id, RigidBody::new(members.to_vec(), collision_group, &self.objects, &indices)));
+
+        return Some(id);
+
+    }
+
+    /// Lets the members of a rigid body move freely again. They go back to colliding with each other.
+    pub fn remove_rigid_body(&mut self, id: RigidBodyId) -> Option<RigidBody> {
+
+        let position = self.rigid_bodies.iter().position(|(body_id, _)| *body_id == id)?;
+        let (_, body) = self.rigid_bodies.remove(position);
+        self.joints.retain(|(_, joint)| {
+            let (a, b) = joint.get_bodies();
+            a != JointBody::Rigid(id) && b != JointBody::Rigid(id)
+        });
+
+        for &handle in body.get_members() {
+            if let Some(index) = self.get_index(handle) {
+                self.objects[index].set_collision_group(None);
+                self.objects[index].wake();
+            }
+        }
+
+        return Some(body);
+
+    }
+
+    pub fn get_rigid_body(&self, id: RigidBodyId) -> Option<&RigidBody> {
+
+        self.rigid_bodies.iter().find(|(body_id, _)| *body_id == id).map(|(_, body)| body)
+
+    }
+
+    pub fn get_rigid_body_ids(&self) -> Vec<RigidBodyId> {
+
+        self.rigid_bodies.iter().map(|(id, _)| *id).collect()
+
+    }
+
+    /// Snaps every rigid body back into shape, returning the pairs of members it linked so islands keep them together.
+    fn solve_rigid_bodies(&mut self) -> Vec<(usize, usize)> {
+
+        let mut linked = Vec::new();
+
+        for (_, body) in self.rigid_bodies.iter_mut() {
+
+            let indices: Vec<usize> = body.get_members().iter().filter_map(|&handle| resolve_handle(&self.handle_slots, handle)).collect();
+
+            // A body sleeps and wakes as one.
+            if indices.iter().all(|&index| self.objects[index].is_sleeping()) {
+                continue;
+            }
+
+            for &index in indices.iter() {
+                self.objects[index].wake();
+            }
+
+            body.solve(&mut self.objects, &indices);
+
+            for pair in indices.windows(2) {
+                linked.push((pair[0], pair[1]));
+            }
+
+        }
+
+        return linked;
+
+    }
+
+    /// Makes `members` a [`SoftBody`] whose rest shape is their current arrangement. Returns `None` if any of them is
+    /// missing or already part of a body.
+    pub fn add_soft_body(&mut self, members: &[ObjectHandle], stiffness: f64) -> Option<SoftBodyId> {
+
+        if members.is_empty() {
+            return None;
+        }
+
+        let mut indices = Vec::new();
+
+        for &handle in members {
+
+            match self.get_index(handle) {
+                Some(index) if !self.is_body_member(handle) && !indices.contains(&index) => indices.push(index),
+                _ => return None,
+            }
+
+        }
+
+        for &index in indices.iter() {
+            self.objects[index].wake();
+        }
+
+        let id = self.next_soft_body_id;
+        self.next_soft_body_id += 1;
+        self.soft_bodies.push((id, SoftBody::new(members.to_vec(), stiffness, &self.objects, &indices)));
+
+        return Some(id);
+
+    }
+
+    pub fn remove_soft_body(&mut self, id: SoftBodyId) -> Option<SoftBody> {
+
+        let position = self.soft_bodies.iter().position(|(body_id, _)| *body_id == id)?;
+        let (_, body) = self.soft_bodies.remove(position);
+
+        return Some(body);
+
+    }
+
+    pub fn get_soft_body(&self, id: SoftBodyId) -> Option<&SoftBody> {
+
+        self.soft_bodies.iter().find(|(body_id, _)| *body_id == id).map(|(_, body)| body)
+
+    }
+
+    pub fn get_soft_body_mut(&mut self, id: SoftBodyId) -> Option<&mut SoftBody> {
+
+        self.soft_bodies.iter_mut().find(|(body_id, _)| *body_id == id).map(|(_, body)| body)
+
+    }
+
+    pub fn get_soft_body_ids(&self) -> Vec<SoftBodyId> {
+
+        self.soft_bodies.iter().map(|(id, _)| *id).collect()
+
+    }
+
+    /// Pulls every soft body towards its rest shape, returning the pairs of members it linked so islands keep them
+    /// together.
+    fn solve_soft_bodies(&mut self) -> Vec<(usize, usize)> {
+
+        let mut linked = Vec::new();
+
+        for (_, body) in self.soft_bodies.iter_mut() {
+
+            let indices: Vec<usize> = body.get_members().iter().filter_map(|&handle| resolve_handle(&self.handle_slots, handle)).collect();
+
+            if indices.iter().all(|&index| self.objects[index].is_sleeping()) {
+                continue;
+            }
+
+            for &index in indices.iter() {
+                self.objects[index].wake();
+            }
+
+            body.solve(&mut self.objects, &indices);
+
+            for pair in indices.windows(2) {
+                linked.push((pair[0], pair[1]));
+            }
+
+        }
+
+        return linked;
+
+    }
+
+    /// Adds a balloon whose members are already in the world. Returns `None` if it has fewer than three of them or
+    /// any is missing.
+    pub fn add_balloon(&mut self, balloon: Balloon) -> Option<BalloonId> {
+
+        if balloon.get_members().len() < 3 || !balloon.get_members().iter().all(|&handle| self.contains_object(handle)) {
+            return None;
+        }
+
+        for &handle in balloon.get_members() {
+            self.wake_object(handle);
+        }
+
+        let id = self.next_balloon_id;
+        self.next_balloon_id += 1;
+        self.balloons.push((id, balloon));
+
+        return Some(id);
+
+    }
+
+    /// Lets the gas out of a balloon. Its members and the constraints between them stay.
+    pub fn remove_balloon(&mut self, id: BalloonId) -> Option<Balloon> {
+
+        let position = self.balloons.iter().position(|(balloon_id, _)| *balloon_id == id)?;
+        let (_, balloon) = self.balloons.remove(position);
+
+        for &handle in balloon.get_members() {
+            self.wake_object(handle);
+        }
+
+        return Some(balloon);
+
+    }
+
+    pub fn get_balloon(&self, id: BalloonId) -> Option<&Balloon> {
+
+        self.balloons.iter().find(|(balloon_id, _)| *balloon_id == id).map(|(_, balloon)| balloon)
+
+    }
+
+    /// Wakes the balloon's members, since a change of pressure may set them moving.
+    pub fn get_balloon_mut(&mut self, id: BalloonId) -> Option<&mut Balloon> {
+
+        let position = self.balloons.iter().position(|(balloon_id, _)| *balloon_id == id)?;
+        let members = self.balloons[position].1.get_members().clone();
+
+        for handle in members {
+            self.wake_object(handle);
+        }
+
+        return Some(&mut self.balloons[position].1);
+
+    }
+
+    pub fn get_balloon_ids(&self) -> Vec<BalloonId> {
+
+        self.balloons.iter().map(|(id, _)| *id).collect()
+
+    }
+
+    fn apply_balloon_pressure(&mut self) {
+
+        for (_, balloon) in self.balloons.iter() {
+
+            let indices: Vec<usize> = balloon.get_members().iter().filter_map(|&handle| resolve_handle(&self.handle_slots, handle)).collect();
+
+            if indices.iter().all(|&index| self.objects[index].is_sleeping()) {
+                continue;
+            }
+
+            balloon.apply_pressure(&mut self.objects, &indices);
+
+        }
+
+    }
+
+    fn get_joint_frame(&self, body: JointBody) -> Option<BodyFrame> {
+
+        match body {
+            JointBody::World => Some(BodyFrame::world()),
+            JointBody::Object(handle) => {
+
+                let index = self.get_index(handle)?;
+                let object = &self.objects[index];
+                Some(BodyFrame::rigid(&self.objects, vec![index], object.get_position(), object.get_angle()))
+
+            }
+            JointBody::Rigid(id) => {
+
+                let body = self.get_rigid_body(id)?;
+                let indices: Vec<usize> = body.get_members().iter().filter_map(|&handle| self.get_index(handle)).collect();
+                let (center, angle) = body.frame(&self.objects, &indices);
+                Some(BodyFrame::rigid(&self.objects, indices, center, angle))
+
+            }
+        }
+
+    }
+
+    /// Hinges `a` and `b` together at `anchor`, in world coordinates. Returns `None` if either side is missing or
+    /// both are the same.
+    pub fn add_hinge_joint(&mut self, a: JointBody, b: JointBody, anchor: Vector2<f64>) -> Option<JointId> {
+
+        if a == b {
+            return None;
+        }
+
+        let frame_a = self.get_joint_frame(a)?;
+        let frame_b = self.get_joint_frame(b)?;
+
+        for &index in frame_a.indices.iter().chain(frame_b.indices.iter()) {
+            self.objects[index].wake();
+        }
+
+        let id = self.next_joint_id;
+        self.next_joint_id += 1;
+        self.joints.push((id, HingeJoint::new(a, b, anchor, &frame_a, &frame_b)));
+
+        return Some(id);
+
+    }
+
+    pub fn remove_hinge_joint(&mut self, id: JointId) -> Option<HingeJoint> {
+
+        let position = self.joints.iter().position(|(joint_id, _)| *joint_id == id)?;
+        let (_, joint) = self.joints.remove(position);
+
+        let (a, b) = joint.get_bodies();
+        for frame in [self.get_joint_frame(a), self.get_joint_frame(b)].into_iter().flatten() {
+            for index in frame.indices {
+                self.objects[index].wake();
+            }
+        }
+
+        return Some(joint);
+
+    }
+
+    pub fn get_hinge_joint(&self, id: JointId) -> Option<&HingeJoint> {
+
+        self.joints.iter().find(|(joint_id, _)| *joint_id == id).map(|(_, joint)| joint)
+
+    }
+
+    /// Wakes both sides of the joint, since a new limit or motor may set them moving.
+    pub fn get_hinge_joint_mut(&mut self, id: JointId) -> Option<&mut HingeJoint> {
+
+        let position = self.joints.iter().position(|(joint_id, _)| *joint_id == id)?;
+
+        let (a, b) = self.joints[position].1.get_bodies();
+        for frame in [self.get_joint_frame(a), self.get_joint_frame(b)].into_iter().flatten() {
+            for index in frame.indices {
+                self.objects[index].wake();
+            }
+        }
+
+        return Some(&mut self.joints[position].1);
+
+    }
+
+    pub fn get_hinge_joint_ids(&self) -> Vec<JointId> {
+
+        self.joints.iter().map(|(id, _)| *id).collect()
+
+    }
+
+    /// Solves every joint a few times over, returning the pairs of objects they linked so islands keep them together.
+    fn solve_joints(&mut self, d_t: f64) -> Vec<(usize, usize)> {
+
+        let mut linked = Vec::new();
+
+        for iteration in 0..JOINT_ITERATIONS {
+
+            for joint_index in 0..self.joints.len() {
+
+                let (a, b) = self.joints[joint_index].1.get_bodies();
+                let (mut frame_a, mut frame_b) = match (self.get_joint_frame(a), self.get_joint_frame(b)) {
+                    (Some(frame_a), Some(frame_b)) => (frame_a, frame_b),
+                    _ => continue,
+                };
+
+                let members = frame_a.indices.iter().chain(frame_b.indices.iter());
+                let driven = self.joints[joint_index].1.get_motor().map_or(false, |motor| motor.speed != 0.);
+
+                if !driven && members.clone().all(|&index| self.objects[index].is_sleeping()) {
+                    continue;
+                }
+
+                for &index in members {
+                    self.objects[index].wake();
+                }
+
+                if iteration == 0 {
+
+                    self.joints[joint_index].1.drive(&mut self.objects, &mut frame_a, &mut frame_b, d_t);
+
+                    if let (Some(&index_a), Some(&index_b)) = (frame_a.indices.first(), frame_b.indices.first()) {
+                        linked.push((index_a, index_b));
+                    }
+
+                }
+
+                self.joints[joint_index].1.solve(&mut self.objects, &mut frame_a, &mut frame_b);
+
+            }
+
+        }
+
+        return linked;
+
+    }
+
+    /// How many seconds the world has been updated for. Motions are sampled against this clock.
+    pub fn get_time(&self) -> f64 {
+
+        self.time
+
+    }
+
+    /// Makes the object kinematic, following `motion` from where it is now as its place at time zero, or returns it
+    /// to ordinary dynamics with `None`. Returns `false` if the object is missing.
+    pub fn set_object_motion(&mut self, handle: ObjectHandle, motion: Option<Motion>) -> bool {
+
+        let index = match self.get_index(handle) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        self.kinematic_objects.retain(|entry| entry.handle != handle);
+
+        let object = &mut self.objects[index];
+        object.wake();
+        object.set_kinematic(motion.is_some());
+
+        if let Some(motion) = motion {
+
+            let pose = motion.sample(0.);
+
+            self.kinematic_objects.push(KinematicEntry {
+                handle,
+                local_position: pose.inverse_apply(object.get_position()),
+                local_angle: object.get_angle() - pose.angle,
+                motion,
+            });
+
+        }
+
+        return true;
+
+    }
+
+    pub fn get_object_motion(&self, handle: ObjectHandle) -> Option<&Motion> {
+
+        self.kinematic_objects.iter().find(|entry| entry.handle == handle).map(|entry| &entry.motion)
+
+    }
+
+    /// Moves kinematic colliders and objects to where their motions put them now. Their previous places are kept, so
+    /// they have a velocity to carry and push things with.
+    fn update_kinematics(&mut self) {
+
+        for collider in self.static_colliders.iter_mut() {
+            collider.advance(self.time);
+        }
+
+        for entry in self.kinematic_objects.iter() {
+
+            let index = match resolve_handle(&self.handle_slots, entry.handle) {
+                Some(index) => index,
+                None => continue,
+            };
+
+            let pose = entry.motion.sample(self.time);
+            let position = pose.apply(entry.local_position);
+            let angle = pose.angle + entry.local_angle;
+            let old_position = self.objects[index].get_position();
+
+            let old_cell = self.convert_to_grid_index(old_position.x, old_position.y);
+            let new_cell = self.convert_to_grid_index(position.x, position.y);
+
+            let object = &mut self.objects[index];
+            let old_angle = object.get_angle();
+
+            object.wake();
+            object.set_raw_position(position);
+            object.set_prev_position(old_position);
+            object.set_raw_angle(angle);
+            object.set_angular_velocity(angle - old_angle);
+
+            if old_cell != new_cell {
+                self.grid[old_cell].retain(|&other| other != index);
+                self.grid[new_cell].push(index);
+            }
+
+        }
+
+    }
+
+    pub fn add_portal_pair(&mut self, pair: PortalPair) -> PortalId {
+
+        let id = self.next_portal_id;
+        self.next_portal_id += 1;
+        self.portal_pairs.push((id, pair));
+
+        return id;
+
+    }
+
+    pub fn remove_portal_pair(&mut self, id: PortalId) -> Option<PortalPair> {
+
+        let position = self.portal_pairs.iter().position(|(pair_id, _)| *pair_id == id)?;
+        let (_, pair) = self.portal_pairs.remove(position);
+
+        return Some(pair);
+
+    }
+
+    pub fn get_portal_pair(&self, id: PortalId) -> Option<&PortalPair> {
+
+        self.portal_pairs.iter().find(|(pair_id, _)| *pair_id == id).map(|(_, pair)| pair)
+
+    }
+
+    pub fn get_portal_pair_ids(&self) -> Vec<PortalId> {
+
+        self.portal_pairs.iter().map(|(id, _)| *id).collect()
+
+    }
+
+    pub fn add_emitter(&mut self, emitter: Emitter) -> EmitterId {
+
+        let id = self.next_emitter_id;
+        self.next_emitter_id += 1;
+        self.emitters.push((id, emitter));
+
+        return id;
+
+    }
+
+    pub fn remove_emitter(&mut self, id: EmitterId) -> Option<Emitter> {
+
+        let position = self.emitters.iter().position(|(emitter_id, _)| *emitter_id == id)?;
+        let (_, emitter) = self.emitters.remove(position);
+
+        return Some(emitter);
+
+    }
+
+    pub fn get_emitter(&self, id: EmitterId) -> Option<&Emitter> {
+
+        self.emitters.iter().find(|(emitter_id, _)| *emitter_id == id).map(|(_, emitter)| emitter)
+
+    }
+
+    pub fn get_emitter_mut(&mut self, id: EmitterId) -> Option<&mut Emitter> {
+
+        self.emitters.iter_mut().find(|(emitter_id, _)| *emitter_id == id).map(|(_, emitter)| emitter)
+
+    }
+
+    pub fn get_emitter_ids(&self) -> Vec<EmitterId> {
+
+        self.emitters.iter().map(|(id, _)| *id).collect()
+
+    }
+
+    pub fn add_sink(&mut self, sink: Sink) -> SinkId {
+
+        let id = self.next_sink_id;
+        self.next_sink_id += 1;
+        self.sinks.push((id, sink));
+
+        return id;
+
+    }
+
+    pub fn remove_sink(&mut self, id: SinkId) -> Option<Sink> {
+
+        let position = self.sinks.iter().position(|(sink_id, _)| *sink_id == id)?;
+        let (_, sink) = self.sinks.remove(position);
+
+        return Some(sink);
+
+    }
+
+    pub fn get_sink(&self, id: SinkId) -> Option<&Sink> {
+
+        self.sinks.iter().find(|(sink_id, _)| *sink_id == id).map(|(_, sink)| sink)
+
+    }
+
+    pub fn get_sink_mut(&mut self, id: SinkId) -> Option<&mut Sink> {
+
+        self.sinks.iter_mut().find(|(sink_id, _)| *sink_id == id).map(|(_, sink)| sink)
+
+    }
+
+    pub fn get_sink_ids(&self) -> Vec<SinkId> {
+
+        self.sinks.iter().map(|(id, _)| *id).collect()
+
+    }
+
+    /// The most objects emitters may fill the world with. Emitters skip whatever would go over it.
+    pub fn get_particle_budget(&self) -> Option<usize> {
+
+        self.particle_budget
+
+    }
+
+    pub fn set_particle_budget(&mut self, particle_budget: Option<usize>) {
+
+        self.particle_budget = particle_budget;
+
+    }
+
+    /// Removes objects that have outlived their lifetimes or wandered into a sink, then lets the emitters spawn.
+    fn update_emitters(&mut self, d_t: f64) {
+
+        let mut expired = Vec::new();
+
+        for index in 0..self.objects.len() {
+
+            let object = &mut self.objects[index];
+            let mut remove = object.advance_age(d_t);
+
+            if !object.is_kinematic() {
+                if let Some((_, sink)) = self.sinks.iter_mut().find(|(_, sink)| sink.get_region().contains(object.get_position())) {
+                    sink.count_removed();
+                    remove = true;
+                }
+            }
+
+            if remove {
+                expired.push(self.object_handles[index]);
+            }
+
+        }
+
+        if !expired.is_empty() {
+            self.remove_objects(&expired);
+        }
+
+        if self.emitters.is_empty() {
+            return;
+        }
+
+        let mut rng = thread_rng();
+        let mut spawned = Vec::new();
+
+        for (_, emitter) in self.emitters.iter_mut() {
+
+            let count = emitter.take_spawn_count(d_t);
+            let room = self.particle_budget.map_or(usize::MAX, |budget| budget.saturating_sub(self.objects.len() + spawned.len()));
+
+            for _ in 0..count.min(room) {
+                spawned.push(emitter.spawn(&mut rng, d_t));
+            }
+
+        }
+
+        for circle in spawned {
+            let shape = CircleShape::new(circle.get_size() as f32, 16);
+            self.push_object(circle, shape);
+        }
+
+    }
+
+    pub fn get_mouse_joint(&self) -> Option<&MouseJoint> {
+
+        self.mouse_joint.as_ref()
+
+    }
+
+    pub fn get_mouse_joint_mut(&mut self) -> Option<&mut MouseJoint> {
+
+        self.mouse_joint.as_mut()
+
+    }
+
+    /// Starts dragging with `mouse_joint`, replacing any drag in progress, or lets go with `None`.
+    pub fn set_mouse_joint(&mut self, mouse_joint: Option<MouseJoint>) {
+
+        self.mouse_joint = mouse_joint;
+
+    }
+
+    pub fn is_cohesion_enabled(&self) -> bool {
+
+        self.cohesion_enabled
+
+    }
+
+    /// Lets touching objects with sticky materials bond together.
+    pub fn set_cohesion_enabled(&mut self, cohesion_enabled: bool) {
+
+        self.cohesion_enabled = cohesion_enabled;
+
+    }
+
+    /// The relative speed, in units per second, below which touching sticky objects bond.
+    pub fn get_bond_speed_threshold(&self) -> f64 {
+
+        self.bond_speed_threshold
+
+    }
+
+    pub fn set_bond_speed_threshold(&mut self, bond_speed_threshold: f64) {
+
+        self.bond_speed_threshold = bond_speed_threshold;
+
+    }
+
+    /// The strain at which a bond between two perfectly sticky materials breaks. Less sticky bonds break sooner.
+    pub fn get_bond_breaking_strain(&self) -> f64 {
+
+        self.bond_breaking_strain
+
+    }
+
+    pub fn set_bond_breaking_strain(&mut self, bond_breaking_strain: f64) {
+
+        self.bond_breaking_strain = bond_breaking_strain;
+
+    }
+
+    /// Draws every bond as a line, shading from green to red as it nears breaking.
+    pub fn set_debug_draw_bonds(&mut self, debug_draw_bonds: bool) {
+
+        self.debug_draw_bonds = debug_draw_bonds;
+
+    }
+
+    pub fn is_thermal_enabled(&self) -> bool {
+
+        self.thermal_enabled
+
+    }
+
+    /// Lets heat flow between touching objects, from hot walls and heat sources, and lets objects expand and melt
+    /// with temperature.
+    pub fn set_thermal_enabled(&mut self, thermal_enabled: bool) {
+
+        self.thermal_enabled = thermal_enabled;
+
+    }
+
+    pub fn add_heat_source(&mut self, source: HeatSource) -> HeatSourceId {
+
+        let id = self.next_heat_source_id;
+        self.next_heat_source_id += 1;
+        self.heat_sources.push((id, source));
+
+        return id;
+
+    }
+
+    pub fn remove_heat_source(&mut self, id: HeatSourceId) -> Option<HeatSource> {
+
+        let position = self.heat_sources.iter().position(|(source_id, _)| *source_id == id)?;
+
+        Some(self.heat_sources.remove(position).1)
+
+    }
+
+    pub fn get_heat_sources(&self) -> &Vec<(HeatSourceId, HeatSource)> {
+
+        &self.heat_sources
+
+    }
+
+    pub fn get_heat_source_mut(&mut self, id: HeatSourceId) -> Option<&mut HeatSource> {
+
+        self.heat_sources.iter_mut().find(|(source_id, _)| *source_id == id).map(|(_, source)| source)
+
+    }
+
+    /// The relative speed, in units per second, below which touching objects merge into one, or `None` if they never do.
+    pub fn get_merge_speed_threshold(&self) -> Option<f64> {
+
+        self.merge_speed_threshold
+
+    }
+
+    pub fn set_merge_speed_threshold(&mut self, merge_speed_threshold: Option<f64>) {
+
+        self.merge_speed_threshold = merge_speed_threshold;
+
+    }
+
+    pub(crate) fn push_event(&mut self, event: PhysicsEvent) {
+
+        self.events.push(event);
+
+    }
+
+    /// Returns everything that happened since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<PhysicsEvent> {
+
+        std::mem::take(&mut self.events)
+
+    }
+
+    pub fn update (&mut self, d_t: f64) {
+
+        self.time_step = d_t;
+        self.time += d_t;
+        self.update_kinematics();
+
+        let mut contacts: Vec<(usize, usize)> = Vec::new();
+        let mut merges: Vec<(ObjectHandle, ObjectHandle)> = Vec::new();
+
+        for object_index in 0..self.objects.len() as usize {
+            let object = &mut self.objects[object_index];
+            let object_position = object.get_position();
+            let grid_index = self.convert_to_grid_index(object_position.x, object_position.y);
+
+            for grid_x_offset in 0..3 {
+                for grid_y_offset in 0..3 {
+
+                    let offset_index : usize = (grid_index as i32 + (grid_x_offset as i32 - 1) + (grid_y_offset as i32 - 1) * GRID_SIZE as i32) as usize;
+
+                    if offset_index >= GRID_SIZE * GRID_SIZE {
+                        continue;
+                    }
+
+                    for other_object_index_index in 0..self.grid[offset_index].len() {
+                        let other_object_index = *self.grid[offset_index].get(other_object_index_index).unwrap();
+
+                        // Each pair is met once from either side; resolving it from both would apply friction twice.
+                        if other_object_index <= object_index {
+                            continue;
+                        }
+
+                        let (head, tail) = self.objects.split_at_mut(other_object_index);
+                        let other_object = &mut tail[0];
+                        let object_to_check = &mut head[object_index];
+
+                        if !object_to_check.collides_with(other_object) {
+                            continue;
+                        }
+
+                        let touching = match (object_to_check.is_kinematic(), other_object.is_kinematic()) {
+                            (true, true) => continue,
+                            // Kinematic objects push through anything, waking whatever they push.
+                            (true, false) => {
+                                let touching = other_object.resolve_collision_with_fixed(object_to_check);
+                                if touching {
+                                    other_object.wake();
+                                }
+                                touching
+                            }
+                            (false, true) => {
+                                let touching = object_to_check.resolve_collision_with_fixed(other_object);
+                                if touching {
+                                    object_to_check.wake();
+                                }
+                                touching
+                            }
+                            (false, false) => match (object_to_check.is_sleeping(), other_object.is_sleeping()) {
+                                (true, true) => continue,
+                                (true, false) => other_object.resolve_collision_with_fixed(object_to_check),
+                                (false, true) => object_to_check.resolve_collision_with_fixed(other_object),
+                                (false, false) => object_to_check.resolve_collision(other_object),
+                            },
+                        };
+
+                        if touching && (self.sleeping_enabled || self.cohesion_enabled || self.thermal_enabled) {
+                            contacts.push((object_index, other_object_index));
+                        }
+
+                        if let (true, Some(threshold)) = (touching, self.merge_speed_threshold) {
+                            if (object_to_check.get_velocity() - other_object.get_velocity()).magnitude() < threshold * d_t {
+                                merges.push((self.object_handles[object_index], self.object_handles[other_object_index]));
+                            }
+                        }
+
+                    }
+                }
+            }
+
+        }
+
+
+        //self.objects.sort_by(|a, b| {
+        //    if a.get_position().y < b.get_position().y {
+        //        return Ordering::Less;
+        //    } else if a.get_position().y > b.get_position().y {
+        //        return Ordering::Greater;
+        //    } else {
+        //        if a.get_position().x < b.get_position().x {
+        //            return Ordering::Less;
+        //        } else if a.get_position().x > b.get_position().x {
+        //            return Ordering::Greater;
+        //        } else {
+        //            return Ordering::Equal;
+        //        }
+        //    }
+        //});
+
+        let portal_pairs: Vec<PortalPair> = self.portal_pairs.iter().map(|(_, pair)| *pair).collect();
+
+        if !portal_pairs.is_empty() {
+            self.resolve_portal_ghosts(&portal_pairs);
+        }
+
+        for object in self.objects.iter_mut() {
+
+            // Kinematic objects go where their motion says, walls or not.
+            if object.is_kinematic() {
+                continue;
+            }
+
+            if object.is_sleeping() {
+
+                // A moving collider still has to shove resting objects out of its way.
+                let shoved = self.static_colliders.iter()
+                    .any(|collider| collider.is_moving() && collider.contact(object.get_position(), object.get_size()).is_some());
+
+                if !shoved {
+                    continue;
+                }
+
+                object.wake();
+
+            }
+
+            for collider in self.static_colliders.iter() {
+
+                // Portals are usually cut into walls, and anything going through must be let past the wall.
+                if !portal_pairs.is_empty() {
+                    if let Some((normal, _)) = collider.contact(object.get_position(), object.get_size()) {
+                        let contact_point = object.get_position() - normal * object.get_size();
+                        let in_portal = portal_pairs.iter()
+                            .flat_map(|pair| pair.directions())
+                            .any(|(portal, _)| portal.is_in_aperture(contact_point, object.get_size()) && portal.is_entered_by(object));
+                        if in_portal {
+                            continue;
+                        }
+                    }
+                }
+
+                let touching = collider.resolve_collision(object, d_t);
+
+                if let (true, true, Some(temperature)) = (touching, self.thermal_enabled, collider.get_temperature()) {
+                    conduct_heat_from_wall(object, temperature, d_t);
+                }
+
+            }
+
+        }
+
+        if self.thermal_enabled {
+            self.conduct_heat(&contacts, d_t);
+            self.apply_heat_sources(d_t);
+            self.apply_thermal_expansion();
+        }
+
+        if self.cohesion_enabled {
+            self.form_bonds(&contacts, d_t);
+        }
+
+        let mut linked = self.solve_distance_constraints();
+        linked.extend(self.solve_rigid_bodies());
+        linked.extend(self.solve_soft_bodies());
+        linked.extend(self.solve_joints(d_t));
+
+        if self.sleeping_enabled {
+            contacts.extend(linked);
+            self.update_sleeping(&contacts, d_t);
+        }
+
+        self.apply_force_generators(d_t);
+        self.apply_balloon_pressure();
+
+        if let Some(mouse_joint) = self.mouse_joint.take() {
+            mouse_joint.apply(self, d_t);
+            self.mouse_joint = Some(mouse_joint);
+        }
+        self.apply_species_interactions();
+
+        for index in 0..GRID_SIZE * GRID_SIZE {
+            self.grid[index].clear();
+        }
+
+        let mut swept = Vec::new();
+
+        for object_index in 0..self.objects.len() as usize {
+
+            if self.objects[object_index].is_sleeping() || self.objects[object_index].is_kinematic() {
+
+                self.objects[object_index].set_force(Vector2::zero());
+                let grid_index = self.convert_to_grid_index(self.objects[object_index].get_position().x, self.objects[object_index].get_position().y);
+                self.grid[grid_index].push(object_index);
+                continue;
+
+            }
+
+            self.objects[object_index].update(d_t);
+
+            if self.objects[object_index].is_ccd_enabled() {
+                swept.push(object_index);
+            }
+
+            if !portal_pairs.is_empty() {
+                teleport_through_portals(&portal_pairs, &mut self.objects[object_index]);
+            }
+
+            (self.update_predicate)(&mut self.objects[object_index]);
+            let grid_index = self.convert_to_grid_index(self.objects[object_index].get_position().x, self.objects[object_index].get_position().y);
+            self.grid[grid_index].push(object_index);
+        }
+
+        // Sweeps run once the grid is complete, so they see every object and not just those integrated before them.
+        for object_index in swept {
+
+            let old_position = self.objects[object_index].get_position();
+            let old_cell = self.convert_to_grid_index(old_position.x, old_position.y);
+
+            self.resolve_continuous_collisions(object_index);
+
+            let new_position = self.objects[object_index].get_position();
+            let new_cell = self.convert_to_grid_index(new_position.x, new_position.y);
+
+            if old_cell != new_cell {
+                self.grid[old_cell].retain(|&other| other != object_index);
+                self.grid[new_cell].push(object_index);
+            }
+
+        }
+
+        if !merges.is_empty() {
+            self.merge_objects(&merges);
+        }
+
+        self.update_emitters(d_t);
+
+    }
+
+    pub fn draw(&mut self, window : &mut RenderWindow){
+
+        // With an interaction matrix set, species matter more than whatever the predicate shows.
+        let color_by_species = self.interaction_matrix.is_some();
+
+        for object_index in 0..self.circle_shapes.len() {
+
+            // Objects can change size as they heat up or merge, so keep their shapes in step before the predicate runs.
+            let size = self.objects[object_index].get_size() as f32;
+            if self.circle_shapes[object_index].radius() != size {
+                self.circle_shapes[object_index].set_radius(size);
+            }
+
+            (self.draw_predicate)(&self.objects[object_index], &mut self.circle_shapes[object_index]);
+
+            if color_by_species {
+                self.circle_shapes[object_index].set_fill_color(species_color(self.objects[object_index].get_species()));
+            }
+
+            if let Some(color) = self.objects[object_index].get_color() {
+                self.circle_shapes[object_index].set_fill_color(color);
+            }
+
+            if self.debug_draw_sleeping && self.objects[object_index].is_sleeping() {
+                self.circle_shapes[object_index].set_fill_color(Color::rgb(70, 70, 160));
+            }
+
+            window.draw(&self.circle_shapes[object_index]);
+        }
+
+        if self.debug_draw_rotation {
+
+            let mut vertices = Vec::new();
+
+            for object in self.objects.iter() {
+
+                let center = object.get_position();
+                let rim = center + Vector2::new(object.get_angle().cos(), object.get_angle().sin()) * object.get_size();
+
+                vertices.push(Vertex::with_pos_color(Vector2f::new(center.x as f32, center.y as f32), Color::WHITE));
+                vertices.push(Vertex::with_pos_color(Vector2f::new(rim.x as f32, rim.y as f32), Color::WHITE));
+
+            }
+
+            window.draw_primitives(&vertices, PrimitiveType::LINES, &RenderStates::default());
+
+        }
+
+        if self.debug_draw_bonds {
+
+            let mut vertices = Vec::new();
+
+            for entry in self.distance_constraints.iter().filter(|entry| entry.bond) {
+
+                let (a, b) = entry.constraint.get_objects();
+                let stress = (entry.constraint.get_strain(self) / entry.constraint.get_breaking_strain().unwrap_or(f64::INFINITY)).min(1.);
+                let color = Color::rgb((stress * 255.) as u8, ((1. - stress) * 255.) as u8, 0);
+
+                for handle in [a, b] {
+                    let position = self.get_object(handle).unwrap().get_position();
+                    vertices.push(Vertex::with_pos_color(Vector2f::new(position.x as f32, position.y as f32), color));
+                }
+
+            }
+
+            window.draw_primitives(&vertices, PrimitiveType::LINES, &RenderStates::default());
+
+        }
+
+        for (id, pair) in self.portal_pairs.iter() {
+
+            // Both ends of a pair share a colour so pairs can be told apart.
+            let hue = (*id as f64 * 0.618).fract();
+            let color = Color::rgb((hue * 255.) as u8, 160, ((1. - hue) * 255.) as u8);
+            let (a, b) = pair.get_portals();
+
+            let mut vertices = Vec::new();
+            for portal in [a, b] {
+                let (start, end) = portal.get_endpoints();
+                vertices.push(Vertex::with_pos_color(Vector2f::new(start.x as f32, start.y as f32), color));
+                vertices.push(Vertex::with_pos_color(Vector2f::new(end.x as f32, end.y as f32), color));
+            }
+
+            window.draw_primitives(&vertices, PrimitiveType::LINES, &RenderStates::default());
+
+        }
+
+        for collider in self.static_colliders.iter() {
+            match collider.get_shape() {
+                ColliderShape::Segment { start, end } => {
+                    let vertices = [
+                        Vertex::with_pos_color(Vector2f::new(start.x as f32, start.y as f32), Color::rgb(200, 200, 200)),
+                        Vertex::with_pos_color(Vector2f::new(end.x as f32, end.y as f32), Color::rgb(200, 200, 200)),
+                    ];
+                    window.draw_primitives(&vertices, PrimitiveType::LINES, &RenderStates::default());
+                }
+                ColliderShape::Circle { center, radius } => {
+                    let mut shape = CircleShape::new(radius as f32, 32);
+                    shape.set_origin(Vector2f::new(radius as f32, radius as f32));
+                    shape.set_position(Vector2f::new(center.x as f32, center.y as f32));
+                    shape.set_fill_color(Color::rgb(200, 200, 200));
+                    window.draw(&shape);
+                }
+            }
+        }
+
+        // Visualize the grid
+        /*for x in 0..GRID_SIZE {
+            for y in 0..GRID_SIZE {
+                let mut rect = RectangleShape::new();
+                rect.set_size(Vector2f::new((self.size.0 as f32 / GRID_SIZE as f32), (self.size.1 as f32 / GRID_SIZE as f32)));
+                rect.set_position(Vector2f::new(x as f32 * (self.size.0 as f32 / GRID_SIZE as f32), y as f32 * (self.size.1 as f32 / GRID_SIZE as f32)));
+                let grid_particle_count = self.grid[x + y * GRID_SIZE].len();
+                rect.set_fill_color(Color::rgba((x as f64 / GRID_SIZE as f64 * 255.) as u8, (y as f64 / GRID_SIZE as f64 * 255.) as u8, (grid_particle_count as f64 * 2  as f64 * 255.) as u8, 50));
+                rect.set_outline_thickness(1.0);
+                rect.set_outline_color(Color::rgb(50, 50, 50));
+                window.draw(&rect);
+            }
+        }*/
+
+    }
+
+    pub fn get_objects(&self) -> &Vec<Circle> {
+
+        return &self.objects;
+
+    }
+
+    /// Wakes every object, since any of them may be moved through the returned reference.
+    pub fn get_objects_mut(&mut self) -> &mut Vec<Circle> {
+
+        self.wake_all();
+        &mut self.objects
+
+    }
+
+    /// Like [`PhysicsWorld::get_objects_mut`] without waking anything, for the solver's own passes and force
+    /// generators. Sleeping objects reached this way stay put and ignore any force they are given.
+    pub fn get_objects_mut_no_wake(&mut self) -> &mut Vec<Circle> {
+
+        &mut self.objects
+
+    }
+
+    /// The handle of each object in [`PhysicsWorld::get_objects`], in the same order.
+    pub fn get_handles(&self) -> &Vec<ObjectHandle> {
+
+        &self.object_handles
+
+    }
+
+    /// The handle of the object at `index` in [`PhysicsWorld::get_objects`].
+    pub fn get_handle(&self, index : usize) -> Option<ObjectHandle> {
+
+        self.object_handles.get(index).copied()
+
+    }
+
+    /// Where the object for `handle` currently is in [`PhysicsWorld::get_objects`], or `None` if it was removed.
+    /// Indices change whenever objects are removed, so don't hold on to them across updates.
+    pub fn get_index(&self, handle : ObjectHandle) -> Option<usize> {
+
+        resolve_handle(&self.handle_slots, handle)
+
+    }
+
+    pub fn contains_object(&self, handle : ObjectHandle) -> bool {
+
+        self.get_index(handle).is_some()
+
+    }
+
+    pub fn get_object(&self, handle : ObjectHandle) -> Option<&Circle> {
+
+        self.objects.get(self.get_index(handle)?)
+
+    }
+
+    /// Wakes the object, since it may be moved through the returned reference.
+    pub fn get_object_mut(&mut self, handle : ObjectHandle) -> Option<&mut Circle> {
+
+        let index = self.get_index(handle)?;
+        self.objects[index].wake();
+        self.objects.get_mut(index)
+
+    }
+
+    pub fn get_static_colliders(&self) -> &Vec<StaticCollider> {
+
+        &self.static_colliders
+
+    }
+
+    pub fn get_static_collider(&self, id : StaticColliderId) -> Option<&StaticCollider> {
+
+        self.static_colliders.get(id)
+
+    }
+
+    pub fn get_static_collider_mut(&mut self, id : StaticColliderId) -> Option<&mut StaticCollider> {
+
+        self.static_colliders.get_mut(id)
+
+    }
+
+    pub fn add_static_collider(&mut self, collider: StaticCollider) -> StaticColliderId {
+
+        self.static_colliders.push(collider);
+        self.static_colliders.len() - 1
+
+    }
+
+    /// Adds a segment collider along each edge of the world and returns them as top, right, bottom, left.
+    pub fn add_boundary_walls(&mut self) -> [StaticColliderId; 4] {
+
+        let width = self.size.0 as f64;
+        let height = self.size.1 as f64;
+
+        [
+            self.add_static_collider(StaticCollider::segment(Vector2::new(0., 0.), Vector2::new(width, 0.))),
+            self.add_static_collider(StaticCollider::segment(Vector2::new(width, 0.), Vector2::new(width, height))),
+            self.add_static_collider(StaticCollider::segment(Vector2::new(width, height), Vector2::new(0., height))),
+            self.add_static_collider(StaticCollider::segment(Vector2::new(0., height), Vector2::new(0., 0.))),
+        ]
+
+    }
+
+    pub fn push_object(&mut self, circle: Circle, circle_shape: CircleShape<'static>) -> ObjectHandle {
+
+        let index = self.objects.len();
+
+        let slot = match self.free_handle_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                self.handle_slots.push(HandleSlot { index: None, generation: 0 });
+                (self.handle_slots.len() - 1) as u32
+            }
+        };
+
+        self.handle_slots[slot as usize].index = Some(index);
+        let handle = ObjectHandle {
+            slot,
+            generation: self.handle_slots[slot as usize].generation,
+        };
+
+        let grid_index = self.convert_to_grid_index(circle.get_position().x, circle.get_position().y);
+        self.grid[grid_index].push(index);
+        self.max_object_size = self.max_object_size.max(circle.get_size());
+        self.objects.push(circle);
+        self.object_handles.push(handle);
+        self.circle_shapes.push(circle_shape);
+
+        return handle;
+
+    }
+
+    /// Removes an object, along with any distance constraints attached to it.
+    pub fn remove_object(&mut self, handle: ObjectHandle) -> Option<Circle> {
+
+        self.remove_objects(&[handle]).pop()
+
+    }
+
+    /// Removes several objects at once, which is cheaper than removing them one by one. Handles of objects that are
+    /// already gone are skipped.
+    pub fn remove_objects(&mut self, handles: &[ObjectHandle]) -> Vec<Circle> {
+
+        let mut indices: Vec<usize> = handles.iter().filter_map(|&handle| self.get_index(handle)).collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut removed = Vec::new();
+        let mut grid_in_step = true;
+
+        // Going from the back means `swap_remove` only ever moves objects we are keeping.
+        for &index in indices.iter().rev() {
+
+            // Patch the grid for the removal and the move rather than rebuilding all of it, which would be far
+            // slower than the removal itself when only a few particles expire each update.
+            let last = self.objects.len() - 1;
+            grid_in_step &= self.replace_in_grid(index, None);
+            if index != last {
+                grid_in_step &= self.replace_in_grid(last, Some(index));
+            }
+
+            let handle = self.object_handles[index];
+            let slot = &mut self.handle_slots[handle.slot as usize];
+            slot.index = None;
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free_handle_slots.push(handle.slot);
+
+            removed.push(self.objects.swap_remove(index));
+            self.object_handles.swap_remove(index);
+            self.circle_shapes.swap_remove(index);
+
+            if let Some(&moved) = self.object_handles.get(index) {
+                self.handle_slots[moved.slot as usize].index = Some(index);
+            }
+
+        }
+
+        if !removed.is_empty() {
+
+            let slots = &self.handle_slots;
+            self.distance_constraints.retain(|entry| {
+                let (a, b) = entry.constraint.get_objects();
+                resolve_handle(slots, a).is_some() && resolve_handle(slots, b).is_some()
+            });
+
+            let objects = &self.objects;
+            for (_, body) in self.rigid_bodies.iter_mut() {
+                body.retain_members(objects, |handle| resolve_handle(slots, handle));
+            }
+            self.rigid_bodies.retain(|(_, body)| !body.get_members().is_empty());
+            for (_, body) in self.soft_bodies.iter_mut() {
+                body.retain_members(objects, |handle| resolve_handle(slots, handle));
+            }
+            self.soft_bodies.retain(|(_, body)| !body.get_members().is_empty());
+
+            self.kinematic_objects.retain(|entry| resolve_handle(slots, entry.handle).is_some());
+
+            // Losing any part of the ring pops the balloon.
+            self.balloons.retain(|(_, balloon)| balloon.get_members().iter().all(|&handle| resolve_handle(slots, handle).is_some()));
+
+            let rigid_bodies = &self.rigid_bodies;
+            self.joints.retain(|(_, joint)| {
+                let (a, b) = joint.get_bodies();
+                [a, b].iter().all(|body| match body {
+                    JointBody::World => true,
+                    JointBody::Object(handle) => resolve_handle(slots, *handle).is_some(),
+                    JointBody::Rigid(id) => rigid_bodies.iter().any(|(body_id, _)| body_id == id),
+                })
+            });
+
+            if !grid_in_step {
+                self.rebuild_grid();
+            }
+
+        }
+
+        removed.reverse();
+
+        return removed;
+
+    }
+
+    /// Replaces `index` in the grid cell under that object with `replacement`, or drops it with `None`. Returns
+    /// `false` if the object wasn't in that cell, as happens when something moved it since the grid was filled.
+    fn replace_in_grid(&mut self, index: usize, replacement: Option<usize>) -> bool {
+
+        let position = self.objects[index].get_position();
+        let grid_index = self.convert_to_grid_index(position.x, position.y);
+        let cell = &mut self.grid[grid_index];
+
+        let slot = match cell.iter().position(|&other| other == index) {
+            Some(slot) => slot,
+            None => return false,
+        };
+
+        match replacement {
+            Some(replacement) => cell[slot] = replacement,
+            None => {
+                cell.swap_remove(slot);
+            }
+        }
+
+        return true;
+
+    }
+
+    fn rebuild_grid(&mut self) {
+
+        for index in 0..GRID_SIZE * GRID_SIZE {
+            self.grid[index].clear();
+        }
+
+        for object_index in 0..self.objects.len() {
+            let grid_index = self.convert_to_grid_index(self.objects[object_index].get_position().x, self.objects[object_index].get_position().y);
+            self.grid[grid_index].push(object_index);
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn world_with(count: usize) -> (PhysicsWorld, Vec<ObjectHandle>) {
+
+        let mut world = PhysicsWorld::new(640, 640);
+        let handles = (0..count)
+            .map(|index| world.push_object(Circle::new(Vector2::new(20. + index as f64 * 37., 50. + index as f64 * 11.), 2., 1.), CircleShape::new(2., 8)))
+            .collect();
+
+        return (world, handles);
+
+    }
+
+    /// Whether every object is in the grid exactly once, in the cell under it.
+    fn grid_matches(world: &PhysicsWorld) -> bool {
+
+        let (grid_width, grid_height) = world.get_grid_dimensions();
+        let mut entries = 0;
+
+        for y_index in 0..grid_height {
+            for x_index in 0..grid_width {
+                for &index in world.get_grid_cell(x_index, y_index) {
+
+                    let position = world.get_objects()[index].get_position();
+                    if world.convert_to_grid_coordinates(position.x, position.y) != (x_index, y_index) {
+                        return false;
+                    }
+                    entries += 1;
+
+                }
+            }
+        }
+
+        return entries == world.get_objects().len();
+
+    }
+
+    #[test]
+    fn handles_survive_removing_other_objects() {
+
+        let (mut world, handles) = world_with(6);
+        let positions: Vec<Vector2<f64>> = handles.iter().map(|&handle| world.get_object(handle).unwrap().get_position()).collect();
+
+        let removed = world.remove_objects(&[handles[1], handles[4], handles[1]]);
+
+        assert_eq!(removed.len(), 2);
+        assert_eq!(world.get_objects().len(), 4);
+        assert!(world.get_object(handles[1]).is_none());
+        assert!(world.get_object(handles[4]).is_none());
+
+        for &kept in &[0, 2, 3, 5] {
+            assert_eq!(world.get_object(handles[kept]).unwrap().get_position(), positions[kept]);
+        }
+
+    }
+
+    #[test]
+    fn reused_slots_do_not_revive_old_handles() {
+
+        let (mut world, handles) = world_with(3);
+
+        world.remove_object(handles[0]);
+        let new_handle = world.push_object(Circle::new(Vector2::new(400., 400.), 2., 1.), CircleShape::new(2., 8));
+
+        assert_ne!(new_handle, handles[0]);
+        assert!(world.get_object(handles[0]).is_none());
+        assert_eq!(world.get_object(new_handle).unwrap().get_position(), Vector2::new(400., 400.));
+
+    }
+
+    #[test]
+    fn removal_patches_the_grid() {
+
+        let (mut world, handles) = world_with(8);
+
+        // Removing from the middle moves the last object into the gap, which the grid has to follow.
+        world.remove_objects(&[handles[2], handles[7], handles[0]]);
+        assert!(grid_matches(&world));
+
+        world.remove_object(handles[5]);
+        assert!(grid_matches(&world));
+
+    }
+
+    #[test]
+    fn removal_rebuilds_the_grid_if_objects_moved_since_it_was_filled() {
+
+        let (mut world, handles) = world_with(4);
+
+        let moved = world.get_index(handles[3]).unwrap();
+        world.get_objects_mut_no_wake()[moved].set_raw_position(Vector2::new(600., 600.));
+        world.remove_object(handles[0]);
+
+        assert!(grid_matches(&world));
+
+    }
+
+}
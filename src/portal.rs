@@ -0,0 +1,315 @@
+use std::f64::consts::PI;
+use cgmath::Vector2;
+use crate::circle::Circle;
+use crate::physics_object::PhysicsWorld;
+use crate::rigid_body::rotate;
+
+/// Identifies a pair of portals added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type PortalId = usize;
+
+/// One end of a portal pair: a flat opening of `half_width` either side of `center`, facing along `normal_angle`.
+///
+/// Objects go in by crossing the opening from the side it faces to the side behind it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Portal {
+
+    center: Vector2<f64>,
+    normal_angle: f64,
+    half_width: f64,
+
+}
+
+impl Portal {
+
+    pub fn new(center: Vector2<f64>, normal_angle: f64, half_width: f64) -> Portal {
+        Portal {
+            center,
+            normal_angle,
+            half_width,
+        }
+    }
+
+    pub fn get_center(&self) -> Vector2<f64> {
+        self.center
+    }
+
+    pub fn get_normal_angle(&self) -> f64 {
+        self.normal_angle
+    }
+
+    pub fn get_half_width(&self) -> f64 {
+        self.half_width
+    }
+
+    /// The ends of the opening.
+    pub fn get_endpoints(&self) -> (Vector2<f64>, Vector2<f64>) {
+
+        let along = rotate(Vector2::new(0., self.half_width), self.normal_angle);
+        (self.center - along, self.center + along)
+
+    }
+
+    /// `point` relative to the portal, with x in front of it and y along the opening.
+    pub(crate) fn to_local(&self, point: Vector2<f64>) -> Vector2<f64> {
+        rotate(point - self.center, -self.normal_angle)
+    }
+
+    pub(crate) fn from_local(&self, local: Vector2<f64>) -> Vector2<f64> {
+        self.center + rotate(local, self.normal_angle)
+    }
+
+    /// Whether `point` is in the opening itself, closer than `thickness` to it.
+    pub fn is_in_aperture(&self, point: Vector2<f64>, thickness: f64) -> bool {
+
+        let local = self.to_local(point);
+        local.x.abs() <= thickness && local.y.abs() <= self.half_width
+
+    }
+
+    /// Whether `object` is coming into the portal, rather than up against the back of it: its centre is still in front
+    /// of the opening, or crossed it during the last update and is waiting to be moved through.
+    pub(crate) fn is_entered_by(&self, object: &Circle) -> bool {
+
+        let local = self.to_local(object.get_position());
+        let prev_local = self.to_local(object.get_prev_position());
+
+        local.x > 0. || prev_local.x > 0.
+
+    }
+
+}
+
+/// Two portals joined so that whatever goes into one comes out of the other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PortalPair {
+
+    a: Portal,
+    b: Portal,
+
+}
+
+impl PortalPair {
+
+    pub fn new(a: Portal, b: Portal) -> PortalPair {
+        PortalPair {
+            a,
+            b,
+        }
+    }
+
+    pub fn get_portals(&self) -> (Portal, Portal) {
+        (self.a, self.b)
+    }
+
+    /// Both ways through the pair, as the portal entered and the portal left by.
+    pub(crate) fn directions(&self) -> [(Portal, Portal); 2] {
+        [(self.a, self.b), (self.b, self.a)]
+    }
+
+}
+
+/// Where `point` ends up after going into `entry` and out of `exit`. Going in behind `entry` comes out in front of
+/// `exit`, turned half a turn in the portals' frames so the handedness of anything passing through is kept.
+pub(crate) fn transform_point(entry: &Portal, exit: &Portal, point: Vector2<f64>) -> Vector2<f64> {
+    exit.from_local(-entry.to_local(point))
+}
+
+/// How far anything going into `entry` and out of `exit` is turned.
+pub(crate) fn transform_angle(entry: &Portal, exit: &Portal) -> f64 {
+    exit.normal_angle - entry.normal_angle + PI
+}
+
+/// Moves `object` through whichever portal its centre crossed during the last update, moving its previous position
+/// and angle along with it so its velocity and spin come out turned but otherwise unchanged. Returns whether it went
+/// through one.
+pub(crate) fn teleport_through_portals(pairs: &[PortalPair], object: &mut Circle) -> bool {
+
+    for pair in pairs {
+        for (entry, exit) in pair.directions() {
+
+            let prev_local = entry.to_local(object.get_prev_position());
+            let local = entry.to_local(object.get_position());
+
+            if !(prev_local.x > 0. && local.x <= 0.) {
+                continue;
+            }
+
+            let fraction = prev_local.x / (prev_local.x - local.x);
+            let crossing = prev_local.y + (local.y - prev_local.y) * fraction;
+
+            if crossing.abs() > entry.half_width {
+                continue;
+            }
+
+            let turn = transform_angle(&entry, &exit);
+            let angular_velocity = object.get_angular_velocity();
+
+            object.set_raw_position(transform_point(&entry, &exit, object.get_position()));
+            object.set_prev_position(transform_point(&entry, &exit, object.get_prev_position()));
+            object.set_raw_angle(object.get_angle() + turn);
+            object.set_angular_velocity(angular_velocity);
+
+            return true;
+
+        }
+    }
+
+    return false;
+
+}
+
+impl PhysicsWorld {
+
+    /// Lets objects partway into a portal collide with whatever is near the other end, through a ghost of the part
+    /// of them that is already through.
+    pub(crate) fn resolve_portal_ghosts(&mut self, pairs: &[PortalPair]) {
+
+        let max_object_size = self.get_max_object_size();
+
+        for pair in pairs {
+            for (entry, exit) in pair.directions() {
+
+                let reach = Vector2::new(entry.half_width + max_object_size, entry.half_width + max_object_size);
+                let mut straddlers = Vec::new();
+
+                self.for_each_object_in_region(entry.center - reach, entry.center + reach, |index, object| {
+                    if !object.is_sleeping() && entry.is_in_aperture(object.get_position(), object.get_size()) {
+                        straddlers.push(index);
+                    }
+                });
+
+                let turn = transform_angle(&entry, &exit);
+
+                for straddler in straddlers {
+
+                    // The ghost is the object as it will be once through, velocity and spin turned along with it.
+                    let mut ghost = self.get_objects()[straddler].clone();
+                    let angular_velocity = ghost.get_angular_velocity();
+                    let ghost_position = transform_point(&entry, &exit, ghost.get_position());
+                    let ghost_prev_position = transform_point(&entry, &exit, ghost.get_prev_position());
+                    ghost.set_raw_position(ghost_position);
+                    ghost.set_prev_position(ghost_prev_position);
+                    ghost.set_raw_angle(ghost.get_angle() + turn);
+                    ghost.set_angular_velocity(angular_velocity);
+
+                    let reach = Vector2::new(ghost.get_size() + max_object_size, ghost.get_size() + max_object_size);
+                    let mut neighbours = Vec::new();
+
+                    self.for_each_object_in_region(ghost_position - reach, ghost_position + reach, |index, object| {
+                        // Only the part in front of the exit has come through.
+                        if index != straddler && exit.to_local(object.get_position()).x > 0. && ghost.collides_with(object) {
+                            neighbours.push(index);
+                        }
+                    });
+
+                    for neighbour in neighbours {
+
+                        let other = &mut self.get_objects_mut_no_wake()[neighbour];
+                        let touching = if ghost.is_kinematic() {
+                            other.resolve_collision_with_fixed(&ghost)
+                        } else if other.is_sleeping() || other.is_kinematic() {
+                            ghost.resolve_collision_with_fixed(other)
+                        } else {
+                            ghost.resolve_collision(other)
+                        };
+
+                        if touching {
+                            other.wake();
+                        }
+
+                    }
+
+                    if ghost.is_kinematic() {
+                        continue;
+                    }
+
+                    // Carry whatever pushed or dragged on the ghost back through to the object itself.
+                    let push = rotate(ghost.get_position() - ghost_position, -turn);
+                    let drag = rotate(ghost.get_prev_position() - ghost_prev_position, -turn);
+                    let spin = ghost.get_angular_velocity() - angular_velocity;
+
+                    let object = &mut self.get_objects_mut_no_wake()[straddler];
+                    object.set_raw_position(object.get_position() + push);
+                    object.set_prev_position(object.get_prev_position() + drag);
+                    object.set_angular_velocity(object.get_angular_velocity() + spin);
+
+                }
+
+            }
+        }
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use cgmath::InnerSpace;
+    use super::*;
+
+    fn close(a: Vector2<f64>, b: Vector2<f64>) -> bool {
+        (a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9
+    }
+
+    fn portals() -> (Portal, Portal) {
+        (Portal::new(Vector2::new(0., 0.), 0., 5.), Portal::new(Vector2::new(100., 0.), PI / 2., 5.))
+    }
+
+    #[test]
+    fn behind_the_entry_comes_out_in_front_of_the_exit() {
+
+        let (entry, exit) = portals();
+        let point = transform_point(&entry, &exit, Vector2::new(-1., 2.));
+
+        assert!(close(point, Vector2::new(102., 1.)));
+        assert!(exit.to_local(point).x > 0.);
+
+    }
+
+    #[test]
+    fn handedness_is_kept() {
+
+        let (entry, exit) = portals();
+        let cross = |a: Vector2<f64>, b: Vector2<f64>| a.x * b.y - a.y * b.x;
+
+        // Travelling into the entry is travelling against its normal; coming out is travelling along the exit's.
+        let travel_in = -rotate(Vector2::new(1., 0.), entry.get_normal_angle());
+        let travel_out = rotate(Vector2::new(1., 0.), exit.get_normal_angle());
+
+        for &point in &[Vector2::new(-1., 2.), Vector2::new(-3., -4.)] {
+
+            let through = transform_point(&entry, &exit, point);
+            let offset_in = point - entry.get_center();
+            let offset_out = through - exit.get_center();
+
+            // Whatever was on the traveller's left going in is on its left coming out, just as far along.
+            assert!((cross(travel_in, offset_in) - cross(travel_out, offset_out)).abs() < 1e-9);
+            assert!((travel_in.dot(offset_in) - travel_out.dot(offset_out)).abs() < 1e-9);
+
+        }
+
+    }
+
+    #[test]
+    fn going_back_undoes_the_move() {
+
+        let (entry, exit) = portals();
+        let point = Vector2::new(-3., 4.);
+
+        assert!(close(transform_point(&exit, &entry, transform_point(&entry, &exit, point)), point));
+        assert!((transform_angle(&entry, &exit) + transform_angle(&exit, &entry) - 2. * PI).abs() < 1e-9);
+
+    }
+
+    #[test]
+    fn velocity_into_the_entry_leaves_along_the_exit_normal() {
+
+        let (entry, exit) = portals();
+        let velocity = rotate(Vector2::new(-1., 0.), transform_angle(&entry, &exit));
+
+        assert!(close(velocity, Vector2::new(0., 1.)));
+
+    }
+
+}
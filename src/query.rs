@@ -0,0 +1,303 @@
+use cgmath::{InnerSpace, Vector2};
+use crate::circle::Circle;
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// An axis aligned box given by its minimum and maximum corners.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector2<f64>,
+    pub max: Vector2<f64>,
+}
+
+impl Aabb {
+
+    pub fn new(min: Vector2<f64>, max: Vector2<f64>) -> Aabb {
+        Aabb {
+            min,
+            max,
+        }
+    }
+
+    pub fn from_center(center: Vector2<f64>, half_extents: Vector2<f64>) -> Aabb {
+        Aabb::new(center - half_extents, center + half_extents)
+    }
+
+    pub fn contains(&self, point: Vector2<f64>) -> bool {
+
+        point.x >= self.min.x && point.x <= self.max.x && point.y >= self.min.y && point.y <= self.max.y
+
+    }
+
+    pub fn intersects_circle(&self, center: Vector2<f64>, radius: f64) -> bool {
+
+        let closest = Vector2::new(center.x.clamp(self.min.x, self.max.x), center.y.clamp(self.min.y, self.max.y));
+
+        (center - closest).magnitude2() < radius * radius
+
+    }
+
+}
+
+/// Whether `point` lies inside the simple polygon with the given vertices, in either winding.
+pub fn polygon_contains(vertices: &[Vector2<f64>], point: Vector2<f64>) -> bool {
+
+    let mut inside = false;
+
+    for index in 0..vertices.len() {
+
+        let a = vertices[index];
+        let b = vertices[(index + 1) % vertices.len()];
+
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+
+    }
+
+    return inside;
+
+}
+
+impl PhysicsWorld {
+
+    /// Calls `visitor` with the index and object of everything whose centre lies in a grid cell overlapping `min`..`max`.
+    ///
+    /// Objects are bucketed by centre only, so callers widen the region by the object sizes they care about.
+    pub(crate) fn for_each_object_in_region<F: FnMut(usize, &Circle)>(&self, min: Vector2<f64>, max: Vector2<f64>, mut visitor: F) {
+
+        let (min_x, min_y) = self.convert_to_grid_coordinates(min.x, min.y);
+        let (max_x, max_y) = self.convert_to_grid_coordinates(max.x, max.y);
+
+        for y_index in min_y..=max_y {
+            for x_index in min_x..=max_x {
+                for &index in self.get_grid_cell(x_index, y_index) {
+                    visitor(index, &self.get_objects()[index]);
+                }
+            }
+        }
+
+    }
+
+    /// Returns every object containing `point`.
+    pub fn query_point(&self, point: Vector2<f64>) -> Vec<ObjectHandle> {
+
+        self.query_circle(point, 0.)
+
+    }
+
+    /// Returns every object overlapping the circle of `radius` around `center`.
+    pub fn query_circle(&self, center: Vector2<f64>, radius: f64) -> Vec<ObjectHandle> {
+
+        let reach = radius + self.get_max_object_size();
+        let mut result = Vec::new();
+
+        self.for_each_object_in_region(center - Vector2::new(reach, reach), center + Vector2::new(reach, reach), |index, object| {
+            if object.check_circle_collision(center, radius) {
+                result.push(self.get_handles()[index]);
+            }
+        });
+
+        return result;
+
+    }
+
+    /// Returns every object overlapping `aabb`.
+    pub fn query_aabb(&self, aabb: Aabb) -> Vec<ObjectHandle> {
+
+        let reach = Vector2::new(self.get_max_object_size(), self.get_max_object_size());
+        let mut result = Vec::new();
+
+        self.for_each_object_in_region(aabb.min - reach, aabb.max + reach, |index, object| {
+            if aabb.intersects_circle(object.get_position(), object.get_size()) {
+                result.push(self.get_handles()[index]);
+            }
+        });
+
+        return result;
+
+    }
+
+    /// Returns every object whose centre lies inside the polygon, such as a lasso drawn with the mouse.
+    pub fn query_polygon(&self, vertices: &[Vector2<f64>]) -> Vec<ObjectHandle> {
+
+        let mut result = Vec::new();
+
+        if vertices.len() < 3 {
+            return result;
+        }
+
+        let mut min = vertices[0];
+        let mut max = vertices[0];
+        for vertex in vertices.iter() {
+            min = Vector2::new(min.x.min(vertex.x), min.y.min(vertex.y));
+            max = Vector2::new(max.x.max(vertex.x), max.y.max(vertex.y));
+        }
+
+        self.for_each_object_in_region(min, max, |index, object| {
+            if polygon_contains(vertices, object.get_position()) {
+                result.push(self.get_handles()[index]);
+            }
+        });
+
+        return result;
+
+    }
+
+    /// Returns every object whose centre is within `radius` of `center`.
+    pub fn query_radius(&self, center: Vector2<f64>, radius: f64) -> Vec<ObjectHandle> {
+
+        let mut result = Vec::new();
+
+        self.for_each_object_in_region(center - Vector2::new(radius, radius), center + Vector2::new(radius, radius), |index, object| {
+            if (object.get_position() - center).magnitude2() <= radius * radius {
+                result.push(self.get_handles()[index]);
+            }
+        });
+
+        return result;
+
+    }
+
+    /// Returns every pair of objects whose centres are within `radius` of each other, each pair once.
+    pub fn query_pairs(&self, radius: f64) -> Vec<(ObjectHandle, ObjectHandle)> {
+
+        self.query_index_pairs(radius).into_iter()
+            .map(|(a, b)| (self.get_handles()[a], self.get_handles()[b]))
+            .collect()
+
+    }
+
+    /// Like [`PhysicsWorld::query_pairs`], but gives indices into [`PhysicsWorld::get_objects`], lower index first.
+    pub(crate) fn query_index_pairs(&self, radius: f64) -> Vec<(usize, usize)> {
+
+        let mut pairs = Vec::new();
+        let reach = Vector2::new(radius, radius);
+
+        for (index, object) in self.get_objects().iter().enumerate() {
+
+            let center = object.get_position();
+
+            self.for_each_object_in_region(center - reach, center + reach, |other_index, other_object| {
+                if other_index > index && (other_object.get_position() - center).magnitude2() <= radius * radius {
+                    pairs.push((index, other_index));
+                }
+            });
+
+        }
+
+        return pairs;
+
+    }
+
+    /// Returns up to `k` objects ordered by the distance from their centre to `point`, nearest first.
+    ///
+    /// Searches outwards one ring of grid cells at a time and stops once no unvisited cell can hold a closer object.
+    pub fn query_k_nearest(&self, point: Vector2<f64>, k: usize) -> Vec<ObjectHandle> {
+
+        let mut candidates: Vec<(f64, usize)> = Vec::new();
+
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let (grid_width, grid_height) = self.get_grid_dimensions();
+        let (center_x, center_y) = self.convert_to_grid_coordinates(point.x, point.y);
+        let cell_size = self.get_cell_size();
+        let ring_spacing = cell_size.x.min(cell_size.y);
+        let max_ring = grid_width.max(grid_height) as i64;
+
+        for ring in 0..=max_ring {
+
+            for y_offset in -ring..=ring {
+                for x_offset in -ring..=ring {
+
+                    if x_offset.abs() != ring && y_offset.abs() != ring {
+                        continue;
+                    }
+
+                    let x_index = center_x as i64 + x_offset;
+                    let y_index = center_y as i64 + y_offset;
+
+                    if x_index < 0 || y_index < 0 || x_index >= grid_width as i64 || y_index >= grid_height as i64 {
+                        continue;
+                    }
+
+                    for &index in self.get_grid_cell(x_index as usize, y_index as usize) {
+                        candidates.push(((self.get_objects()[index].get_position() - point).magnitude2(), index));
+                    }
+
+                }
+            }
+
+            if candidates.len() >= k {
+
+                candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+                candidates.truncate(k);
+
+                // Anything in a cell beyond this ring is at least `ring` cells away.
+                let bound = ring as f64 * ring_spacing;
+                if candidates[k - 1].0 <= bound * bound {
+                    break;
+                }
+
+            }
+
+        }
+
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        candidates.truncate(k);
+
+        return candidates.into_iter().map(|(_, index)| self.get_handles()[index]).collect();
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use sfml::graphics::CircleShape;
+    use super::*;
+
+    fn world_with(positions: &[(f64, f64)]) -> (PhysicsWorld, Vec<ObjectHandle>) {
+
+        let mut world = PhysicsWorld::new(640, 640);
+        let handles = positions.iter()
+            .map(|&(x, y)| world.push_object(Circle::new(Vector2::new(x, y), 1., 1.), CircleShape::new(1., 8)))
+            .collect();
+
+        return (world, handles);
+
+    }
+
+    #[test]
+    fn k_nearest_orders_by_distance() {
+
+        let (world, handles) = world_with(&[(130., 100.), (100., 100.), (100., 160.), (110., 100.)]);
+
+        assert_eq!(world.query_k_nearest(Vector2::new(101., 100.), 3), vec![handles[1], handles[3], handles[0]]);
+
+    }
+
+    #[test]
+    fn k_nearest_keeps_searching_past_the_first_ring_with_a_hit() {
+
+        // The corner of the next cell is found a ring earlier than the closer object straight across.
+        let (world, handles) = world_with(&[(103.99, 103.99), (104.1, 100.1)]);
+
+        assert_eq!(world.query_k_nearest(Vector2::new(100.1, 100.1), 1), vec![handles[1]]);
+
+    }
+
+    #[test]
+    fn k_nearest_reaches_far_cells() {
+
+        let (world, handles) = world_with(&[(600., 600.), (20., 20.)]);
+
+        assert_eq!(world.query_k_nearest(Vector2::new(10., 10.), 1), vec![handles[1]]);
+        assert_eq!(world.query_k_nearest(Vector2::new(10., 10.), 5), vec![handles[1], handles[0]]);
+        assert!(world.query_k_nearest(Vector2::new(10., 10.), 0).is_empty());
+
+    }
+
+}
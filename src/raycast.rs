@@ -0,0 +1,278 @@
+use std::collections::HashSet;
+use cgmath::{InnerSpace, Vector2};
+use crate::circle::Circle;
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// Where a ray or swept circle first touches an object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub handle: ObjectHandle,
+    /// The contact point on the surface of the hit object.
+    pub point: Vector2<f64>,
+    /// The surface normal of the hit object at `point`, facing the caster.
+    pub normal: Vector2<f64>,
+    /// How far along the cast direction the contact happened.
+    pub distance: f64,
+}
+
+/// Returns the distance along `direction` at which a circle of `radius` starting at `origin` first touches `object`.
+///
+/// Objects already overlapping the cast at `origin` are ignored.
+fn sweep_circle(origin: Vector2<f64>, direction: Vector2<f64>, radius: f64, object: &Circle) -> Option<f64> {
+
+    let combined_radius = radius + object.get_size();
+    let offset = origin - object.get_position();
+    let b = offset.dot(direction);
+    let c = offset.magnitude2() - combined_radius * combined_radius;
+
+    if c < 0. || b > 0. {
+        return None;
+    }
+
+    let discriminant = b * b - c;
+
+    if discriminant < 0. {
+        return None;
+    }
+
+    return Some(-b - discriminant.sqrt());
+
+}
+
+impl PhysicsWorld {
+
+    /// Returns the first object hit by a ray, ignoring objects `filter` rejects.
+    pub fn raycast(&self, origin: Vector2<f64>, direction: Vector2<f64>, max_distance: f64, filter: &dyn Fn(ObjectHandle, &Circle) -> bool) -> Option<RayHit> {
+
+        self.cast(origin, direction, max_distance, 0., filter, true).into_iter().next()
+
+    }
+
+    /// Returns every object hit by a ray, nearest first.
+    pub fn raycast_all(&self, origin: Vector2<f64>, direction: Vector2<f64>, max_distance: f64, filter: &dyn Fn(ObjectHandle, &Circle) -> bool) -> Vec<RayHit> {
+
+        self.cast(origin, direction, max_distance, 0., filter, false)
+
+    }
+
+    /// Sweeps a circle of `radius` from `origin` and returns the first object it touches.
+    pub fn circle_cast(&self, origin: Vector2<f64>, radius: f64, direction: Vector2<f64>, max_distance: f64, filter: &dyn Fn(ObjectHandle, &Circle) -> bool) -> Option<RayHit> {
+
+        self.cast(origin, direction, max_distance, radius, filter, true).into_iter().next()
+
+    }
+
+    /// Sweeps a circle of `radius` from `origin` and returns every object it touches, nearest first.
+    pub fn circle_cast_all(&self, origin: Vector2<f64>, radius: f64, direction: Vector2<f64>, max_distance: f64, filter: &dyn Fn(ObjectHandle, &Circle) -> bool) -> Vec<RayHit> {
+
+        self.cast(origin, direction, max_distance, radius, filter, false)
+
+    }
+
+    /// Walks the grid cells along the cast with a DDA and tests the objects around each cell.
+    fn cast(&self, origin: Vector2<f64>, direction: Vector2<f64>, max_distance: f64, radius: f64, filter: &dyn Fn(ObjectHandle, &Circle) -> bool, first_only: bool) -> Vec<RayHit> {
+
+        let mut hits: Vec<RayHit> = Vec::new();
+
+        if direction.magnitude2() == 0. || max_distance < 0. {
+            return hits;
+        }
+
+        let direction = direction.normalize();
+        let world_size = Vector2::new(self.get_size().0 as f64, self.get_size().1 as f64);
+
+        // Clip the cast to the grid; objects outside the world are clamped into the border cells anyway.
+        let mut t_start: f64 = 0.;
+        let mut t_end = max_distance;
+
+        for axis in 0..2 {
+
+            if direction[axis] == 0. {
+                if origin[axis] < 0. || origin[axis] > world_size[axis] {
+                    return hits;
+                }
+                continue;
+            }
+
+            let t_a = (0. - origin[axis]) / direction[axis];
+            let t_b = (world_size[axis] - origin[axis]) / direction[axis];
+
+            t_start = t_start.max(t_a.min(t_b));
+            t_end = t_end.min(t_a.max(t_b));
+
+        }
+
+        if t_start > t_end {
+            return hits;
+        }
+
+        let cell_size = self.get_cell_size();
+        let (grid_width, grid_height) = self.get_grid_dimensions();
+        let reach = self.get_max_object_size() + radius;
+        let margin_x = (reach / cell_size.x).ceil() as i64;
+        let margin_y = (reach / cell_size.y).ceil() as i64;
+
+        let entry = origin + direction * t_start;
+        let (cell_x, cell_y) = self.convert_to_grid_coordinates(entry.x, entry.y);
+        let mut cell = [cell_x as i64, cell_y as i64];
+        let mut step = [0i64; 2];
+        let mut t_max = [f64::INFINITY; 2];
+        let mut t_delta = [f64::INFINITY; 2];
+
+        for axis in 0..2 {
+
+            if direction[axis] > 0. {
+                step[axis] = 1;
+                t_max[axis] = ((cell[axis] + 1) as f64 * cell_size[axis] - origin[axis]) / direction[axis];
+                t_delta[axis] = cell_size[axis] / direction[axis];
+            } else if direction[axis] < 0. {
+                step[axis] = -1;
+                t_max[axis] = (cell[axis] as f64 * cell_size[axis] - origin[axis]) / direction[axis];
+                t_delta[axis] = -cell_size[axis] / direction[axis];
+            }
+
+        }
+
+        let mut visited: HashSet<usize> = HashSet::new();
+        let mut best_distance = f64::INFINITY;
+        let mut t_enter = t_start;
+
+        loop {
+
+            // Every object touched before this cell sits within `reach` of a cell we already visited.
+            if first_only && t_enter > best_distance {
+                break;
+            }
+
+            for y_index in (cell[1] - margin_y).max(0)..=(cell[1] + margin_y).min(grid_height as i64 - 1) {
+                for x_index in (cell[0] - margin_x).max(0)..=(cell[0] + margin_x).min(grid_width as i64 - 1) {
+                    for &index in self.get_grid_cell(x_index as usize, y_index as usize) {
+
+                        if !visited.insert(index) {
+                            continue;
+                        }
+
+                        let object = &self.get_objects()[index];
+                        let handle = self.get_handles()[index];
+
+                        let distance = match sweep_circle(origin, direction, radius, object) {
+                            Some(distance) if distance <= max_distance => distance,
+                            _ => continue,
+                        };
+
+                        if !filter(handle, object) {
+                            continue;
+                        }
+
+                        let normal = (origin + direction * distance - object.get_position()).normalize();
+
+                        hits.push(RayHit {
+                            handle,
+                            point: object.get_position() + normal * object.get_size(),
+                            normal,
+                            distance,
+                        });
+                        best_distance = best_distance.min(distance);
+
+                    }
+                }
+            }
+
+            let axis = if t_max[0] < t_max[1] { 0 } else { 1 };
+
+            if t_max[axis] > t_end {
+                break;
+            }
+
+            t_enter = t_max[axis];
+            cell[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+
+            if cell[axis] < 0 || cell[axis] >= [grid_width, grid_height][axis] as i64 {
+                break;
+            }
+
+        }
+
+        hits.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(std::cmp::Ordering::Equal));
+
+        if first_only {
+            hits.truncate(1);
+        }
+
+        return hits;
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use sfml::graphics::CircleShape;
+    use super::*;
+
+    fn row() -> (PhysicsWorld, Vec<ObjectHandle>) {
+
+        let mut world = PhysicsWorld::new(640, 640);
+        let handles = [300., 100., 200.].iter()
+            .map(|&x| world.push_object(Circle::new(Vector2::new(x, 100.), 5., 1.), CircleShape::new(5., 8)))
+            .collect();
+
+        return (world, handles);
+
+    }
+
+    fn any(_: ObjectHandle, _: &Circle) -> bool {
+        true
+    }
+
+    #[test]
+    fn raycast_hits_the_nearest_first() {
+
+        let (world, handles) = row();
+        let hit = world.raycast(Vector2::new(10., 100.), Vector2::new(1., 0.), 500., &any).unwrap();
+
+        assert_eq!(hit.handle, handles[1]);
+        assert!((hit.distance - 85.).abs() < 1e-9);
+        assert!((hit.point - Vector2::new(95., 100.)).magnitude() < 1e-9);
+        assert!((hit.normal - Vector2::new(-1., 0.)).magnitude() < 1e-9);
+
+    }
+
+    #[test]
+    fn raycast_all_is_ordered_and_clipped() {
+
+        let (world, handles) = row();
+
+        let hits: Vec<ObjectHandle> = world.raycast_all(Vector2::new(10., 100.), Vector2::new(1., 0.), 500., &any).iter().map(|hit| hit.handle).collect();
+        assert_eq!(hits, vec![handles[1], handles[2], handles[0]]);
+
+        let hits: Vec<ObjectHandle> = world.raycast_all(Vector2::new(10., 100.), Vector2::new(1., 0.), 190., &any).iter().map(|hit| hit.handle).collect();
+        assert_eq!(hits, vec![handles[1], handles[2]]);
+
+        assert!(world.raycast(Vector2::new(10., 100.), Vector2::new(1., 0.), 80., &any).is_none());
+        assert!(world.raycast(Vector2::new(10., 100.), Vector2::new(-1., 0.), 500., &any).is_none());
+
+    }
+
+    #[test]
+    fn circle_cast_touches_sooner_and_respects_the_filter() {
+
+        let (world, handles) = row();
+        let skip_first = |handle: ObjectHandle, _: &Circle| handle != handles[1];
+
+        let hit = world.circle_cast(Vector2::new(10., 100.), 3., Vector2::new(1., 0.), 500., &any).unwrap();
+        assert_eq!(hit.handle, handles[1]);
+        assert!((hit.distance - 82.).abs() < 1e-9);
+
+        let hit = world.circle_cast(Vector2::new(10., 100.), 3., Vector2::new(1., 0.), 500., &skip_first).unwrap();
+        assert_eq!(hit.handle, handles[2]);
+
+        // Passing 7 above the row only grazes with a radius wide enough to reach.
+        assert!(world.raycast(Vector2::new(10., 107.), Vector2::new(1., 0.), 500., &any).is_none());
+        assert!(world.circle_cast(Vector2::new(10., 107.), 3., Vector2::new(1., 0.), 500., &any).is_some());
+
+    }
+
+}
@@ -0,0 +1,211 @@
+use cgmath::{InnerSpace, Vector2, Zero};
+use crate::circle::Circle;
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+
+/// Identifies a rigid body added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type RigidBodyId = usize;
+
+pub(crate) fn rotate(vector: Vector2<f64>, angle: f64) -> Vector2<f64> {
+
+    let (sin, cos) = angle.sin_cos();
+    Vector2::new(vector.x * cos - vector.y * sin, vector.x * sin + vector.y * cos)
+
+}
+
+/// The mass-weighted centre of `positions`.
+pub(crate) fn center_of_mass(positions: &[Vector2<f64>], masses: &[f64]) -> Vector2<f64> {
+
+    let mut center = Vector2::zero();
+    let mut total_mass = 0.;
+
+    for (position, mass) in positions.iter().zip(masses.iter()) {
+        center += position * *mass;
+        total_mass += mass;
+    }
+
+    return center / total_mass;
+
+}
+
+/// Finds the rotation that best lines `rest_offsets` up with `positions` around `center`, weighted by `masses`.
+///
+/// The answer is searched for as a turn away from `guess`, so passing the last result keeps the angle continuous
+/// instead of wrapping at ±π.
+pub(crate) fn best_fit_rotation(positions: &[Vector2<f64>], masses: &[f64], rest_offsets: &[Vector2<f64>], center: Vector2<f64>, guess: f64) -> f64 {
+
+    let mut cross = 0.;
+    let mut dot = 0.;
+
+    for index in 0..positions.len() {
+
+        let rest = rotate(rest_offsets[index], guess);
+        let offset = positions[index] - center;
+
+        cross += masses[index] * (rest.x * offset.y - rest.y * offset.x);
+        dot += masses[index] * rest.dot(offset);
+
+    }
+
+    if cross == 0. && dot == 0. {
+        return guess;
+    }
+
+    return guess + cross.atan2(dot);
+
+}
+
+/// A group of circles that move and turn together as one solid object.
+///
+/// Each update the members are allowed to move on their own, colliding as usual, and are then snapped back onto the
+/// rigid arrangement that best fits where they ended up. Members never collide with each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RigidBody {
+
+    members: Vec<ObjectHandle>,
+    rest_offsets: Vec<Vector2<f64>>,
+    member_angles: Vec<f64>,
+    collision_group: u32,
+    center: Vector2<f64>,
+    prev_center: Vector2<f64>,
+    angle: f64,
+    prev_angle: f64,
+
+}
+
+impl RigidBody {
+
+    pub(crate) fn new(members: Vec<ObjectHandle>, collision_group: u32, objects: &[Circle], indices: &[usize]) -> RigidBody {
+
+        let mut body = RigidBody {
+            members,
+            rest_offsets: Vec::new(),
+            member_angles: Vec::new(),
+            collision_group,
+            center: Vector2::zero(),
+            prev_center: Vector2::zero(),
+            angle: 0.,
+            prev_angle: 0.,
+        };
+
+        body.capture(objects, indices);
+        body.prev_center = body.center;
+
+        return body;
+
+    }
+
+    /// Takes the members' current arrangement as the body's shape, keeping the body's angle and velocity.
+    pub(crate) fn capture(&mut self, objects: &[Circle], indices: &[usize]) {
+
+        let positions: Vec<Vector2<f64>> = indices.iter().map(|&index| objects[index].get_position()).collect();
+        let masses: Vec<f64> = indices.iter().map(|&index| objects[index].get_mass()).collect();
+        let velocity = self.get_velocity();
+
+        self.center = center_of_mass(&positions, &masses);
+        self.prev_center = self.center - velocity;
+        self.rest_offsets = positions.iter().map(|&position| rotate(position - self.center, -self.angle)).collect();
+        self.member_angles = indices.iter().map(|&index| objects[index].get_angle() - self.angle).collect();
+
+    }
+
+    /// Drops members that are no longer in the world and reshapes the body around the rest.
+    pub(crate) fn retain_members(&mut self, objects: &[Circle], resolve: impl Fn(ObjectHandle) -> Option<usize>) {
+
+        let before = self.members.len();
+        self.members.retain(|&handle| resolve(handle).is_some());
+
+        if self.members.len() != before && !self.members.is_empty() {
+            let indices: Vec<usize> = self.members.iter().filter_map(|&handle| resolve(handle)).collect();
+            self.capture(objects, &indices);
+        }
+
+    }
+
+    /// Moves the members given by `indices` (in the same order as [`RigidBody::get_members`]) back into shape.
+    pub(crate) fn solve(&mut self, objects: &mut [Circle], indices: &[usize]) {
+
+        let positions: Vec<Vector2<f64>> = indices.iter().map(|&index| objects[index].get_position()).collect();
+        let masses: Vec<f64> = indices.iter().map(|&index| objects[index].get_mass()).collect();
+
+        let center = center_of_mass(&positions, &masses);
+        let angle = best_fit_rotation(&positions, &masses, &self.rest_offsets, center, self.angle);
+
+        for (member, &index) in indices.iter().enumerate() {
+
+            // Kinematic members follow their motion; the rest of the body fits around them.
+            if objects[index].is_kinematic() {
+                continue;
+            }
+
+            objects[index].set_raw_position(center + rotate(self.rest_offsets[member], angle));
+            objects[index].set_raw_angle(angle + self.member_angles[member]);
+
+        }
+
+        self.prev_center = self.center;
+        self.center = center;
+        self.prev_angle = self.angle;
+        self.angle = angle;
+
+    }
+
+    /// Where the members given by `indices` put the body right now, as its centre of mass and angle.
+    pub(crate) fn frame(&self, objects: &[Circle], indices: &[usize]) -> (Vector2<f64>, f64) {
+
+        let positions: Vec<Vector2<f64>> = indices.iter().map(|&index| objects[index].get_position()).collect();
+        let masses: Vec<f64> = indices.iter().map(|&index| objects[index].get_mass()).collect();
+
+        let center = center_of_mass(&positions, &masses);
+        let angle = best_fit_rotation(&positions, &masses, &self.rest_offsets, center, self.angle);
+
+        return (center, angle);
+
+    }
+
+    pub fn get_members(&self) -> &Vec<ObjectHandle> {
+        &self.members
+    }
+
+    /// The collision group given to every member.
+    pub fn get_collision_group(&self) -> u32 {
+        self.collision_group
+    }
+
+    pub fn get_center_of_mass(&self) -> Vector2<f64> {
+        self.center
+    }
+
+    /// Like [`Circle::get_velocity`], how far the centre of mass moved over the last update.
+    pub fn get_velocity(&self) -> Vector2<f64> {
+        self.center - self.prev_center
+    }
+
+    /// How far the body has turned from the arrangement it was created with.
+    pub fn get_angle(&self) -> f64 {
+        self.angle
+    }
+
+    pub fn get_angular_velocity(&self) -> f64 {
+        self.angle - self.prev_angle
+    }
+
+    pub fn get_mass(&self, world: &PhysicsWorld) -> f64 {
+
+        self.members.iter()
+            .filter_map(|&handle| world.get_object(handle))
+            .map(|object| object.get_mass())
+            .sum()
+
+    }
+
+    /// The moment of inertia about the centre of mass, counting each member as a solid disc.
+    pub fn get_inertia(&self, world: &PhysicsWorld) -> f64 {
+
+        self.members.iter()
+            .filter_map(|&handle| world.get_object(handle))
+            .map(|object| object.get_inertia() + object.get_mass() * (object.get_position() - self.center).magnitude2())
+            .sum()
+
+    }
+
+}
@@ -0,0 +1,283 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use cgmath::Vector2;
+use sfml::graphics::Color;
+use crate::emitter::{Emitter, EmitterId, Region, Sink, SinkId};
+use crate::physics_object::PhysicsWorld;
+use crate::query::Aabb;
+
+/// Emitters, sinks and a particle budget read from a scene file, ready to add to a world.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+
+    pub emitters: Vec<Emitter>,
+    pub sinks: Vec<Sink>,
+    pub particle_budget: Option<usize>,
+
+}
+
+fn parse_numbers<'a>(words: impl Iterator<Item = &'a str>, count: usize, line: &str) -> io::Result<Vec<f64>> {
+
+    let values: Vec<f64> = words.map(|word| word.parse()).collect::<Result<_, _>>()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("bad number: {}", line)))?;
+
+    // `inf` and `nan` parse fine but would only blow up later, mid-update.
+    if values.iter().any(|value| !value.is_finite()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("numbers must be finite: {}", line)));
+    }
+
+    if values.len() != count {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("expected {} numbers: {}", count, line)));
+    }
+
+    return Ok(values);
+
+}
+
+fn parse_region<'a>(mut words: impl Iterator<Item = &'a str>, line: &str) -> io::Result<Region> {
+
+    match words.next() {
+        Some("point") => {
+            let values = parse_numbers(words, 2, line)?;
+            Ok(Region::Point(Vector2::new(values[0], values[1])))
+        }
+        Some("circle") => {
+            let values = parse_numbers(words, 3, line)?;
+            Ok(Region::Circle { center: Vector2::new(values[0], values[1]), radius: values[2] })
+        }
+        Some("aabb") => {
+            let values = parse_numbers(words, 4, line)?;
+            Ok(Region::Aabb(Aabb::new(Vector2::new(values[0], values[1]), Vector2::new(values[2], values[3]))))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("bad region: {}", line))),
+    }
+
+}
+
+impl Scene {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Scene> {
+
+        Scene::parse(&fs::read_to_string(path)?)
+
+    }
+
+    /// Parses the text format:
+    ///
+    /// ```text
+    /// budget 5000
+    /// emitter circle 320 80 20
+    /// rate 200
+    /// burst 50
+    /// direction 1.57
+    /// spread 0.3
+    /// speed 50 80
+    /// radius 2 4
+    /// mass 1 2
+    /// lifetime 10
+    /// color 255 128 0
+    /// friction 0.5
+    /// sink aabb 0 600 640 640
+    /// ```
+    ///
+    /// Regions are `point x y`, `circle x y radius` or `aabb min_x min_y max_x max_y`. Lines after an `emitter`
+    /// configure that emitter; angles are in radians. Any [`Material`](crate::material::Material) property can be
+    /// set the same way as `friction`. Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> io::Result<Scene> {
+
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut scene = Scene::default();
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or("");
+
+            match keyword {
+                "budget" => {
+                    scene.particle_budget = Some(words.next().and_then(|word| word.parse().ok()).ok_or_else(|| invalid(format!("bad budget: {}", line)))?);
+                }
+                "emitter" => {
+                    scene.emitters.push(Emitter::new(parse_region(words, line)?, 0.));
+                }
+                "sink" => {
+                    scene.sinks.push(Sink::new(parse_region(words, line)?));
+                }
+                _ => {
+
+                    let emitter = scene.emitters.last_mut().ok_or_else(|| invalid(format!("{} given before emitter", keyword)))?;
+
+                    match keyword {
+                        "rate" => emitter.set_rate(parse_numbers(words, 1, line)?[0]),
+                        "burst" => emitter.burst(words.next().and_then(|word| word.parse().ok()).ok_or_else(|| invalid(format!("bad burst: {}", line)))?),
+                        "direction" => emitter.set_direction(parse_numbers(words, 1, line)?[0]),
+                        "spread" => emitter.set_spread(parse_numbers(words, 1, line)?[0]),
+                        "speed" | "radius" | "mass" => {
+                            let values = parse_numbers(words, 2, line)?;
+
+                            if values[0] > values[1] {
+                                return Err(invalid(format!("{} minimum is above its maximum: {}", keyword, line)));
+                            }
+
+                            if keyword != "speed" && values[0] <= 0. {
+                                return Err(invalid(format!("{} must be positive: {}", keyword, line)));
+                            }
+
+                            match keyword {
+                                "speed" => emitter.set_speed(values[0], values[1]),
+                                "radius" => emitter.set_radius(values[0], values[1]),
+                                _ => emitter.set_mass(values[0], values[1]),
+                            }
+                        }
+                        "lifetime" => emitter.set_lifetime(Some(parse_numbers(words, 1, line)?[0])),
+                        "color" => {
+                            let values = parse_numbers(words, 3, line)?;
+
+                            if values.iter().any(|&value| !(0. ..=255.).contains(&value)) {
+                                return Err(invalid(format!("color values must be from 0 to 255: {}", line)));
+                            }
+
+                            emitter.set_color(Some(Color::rgb(values[0] as u8, values[1] as u8, values[2] as u8)));
+                        }
+                        "friction" | "stickiness" | "conductivity" | "heat_capacity" | "thermal_expansion" | "melting_point" => {
+
+                            let value = parse_numbers(words, 1, line)?[0];
+                            let mut material = emitter.get_material();
+
+                            match keyword {
+                                "friction" => material.set_friction(value),
+                                "stickiness" => material.set_stickiness(value),
+                                "conductivity" => material.set_conductivity(value),
+                                "heat_capacity" => material.set_heat_capacity(value),
+                                "thermal_expansion" => material.set_thermal_expansion(value),
+                                _ => material.set_melting_point(value),
+                            }
+
+                            emitter.set_material(material);
+
+                        }
+                        _ => return Err(invalid(format!("unknown line: {}", line))),
+                    }
+
+                }
+            }
+
+        }
+
+        return Ok(scene);
+
+    }
+
+}
+
+impl PhysicsWorld {
+
+    /// Adds everything in `scene`, replacing the particle budget if the scene sets one. Fails without adding anything
+    /// if an emitter would spawn circles too big to fit in the world.
+    pub fn add_scene(&mut self, scene: Scene) -> io::Result<(Vec<EmitterId>, Vec<SinkId>)> {
+
+        let (width, height) = self.get_size();
+        let max_radius = width.min(height) as f64 / 2.;
+
+        if scene.emitters.iter().any(|emitter| emitter.get_radius().1 > max_radius) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("radius must be at most {}", max_radius)));
+        }
+
+        if scene.particle_budget.is_some() {
+            self.set_particle_budget(scene.particle_budget);
+        }
+
+        let emitters = scene.emitters.into_iter().map(|emitter| self.add_emitter(emitter)).collect();
+        let sinks = scene.sinks.into_iter().map(|sink| self.add_sink(sink)).collect();
+
+        return Ok((emitters, sinks));
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parses_emitters_and_sinks() {
+
+        let scene = Scene::parse("
+            # a fountain
+            budget 100
+            emitter point 10 20
+            rate 5
+            radius 2 4
+            mass 1 1
+            color 255 128 0
+            friction 0.25
+            sink aabb 0 600 640 640
+        ").unwrap();
+
+        assert_eq!(scene.particle_budget, Some(100));
+        assert_eq!(scene.emitters.len(), 1);
+        assert_eq!(scene.sinks.len(), 1);
+
+        let emitter = &scene.emitters[0];
+        assert_eq!(emitter.get_region(), Region::Point(Vector2::new(10., 20.)));
+        assert_eq!(emitter.get_rate(), 5.);
+        assert_eq!(emitter.get_radius(), (2., 4.));
+        assert_eq!(emitter.get_color(), Some(Color::rgb(255, 128, 0)));
+        assert_eq!(emitter.get_material().get_friction(), 0.25);
+
+    }
+
+    #[test]
+    fn rejects_unknown_lines() {
+        assert!(Scene::parse("emitter point 0 0\nwobble 3").is_err());
+    }
+
+    #[test]
+    fn rejects_properties_before_an_emitter() {
+        assert!(Scene::parse("rate 5\nemitter point 0 0").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_ranges() {
+
+        assert!(Scene::parse("emitter point 0 0\nradius 0 2").is_err());
+        assert!(Scene::parse("emitter point 0 0\nmass -1 2").is_err());
+        assert!(Scene::parse("emitter point 0 0\nradius 4 2").is_err());
+        assert!(Scene::parse("emitter point 0 0\nspeed 80 50").is_err());
+
+    }
+
+    #[test]
+    fn rejects_numbers_that_are_not_finite() {
+
+        assert!(Scene::parse("emitter point 0 0\nspeed 0 inf").is_err());
+        assert!(Scene::parse("emitter point 0 0\nradius nan nan").is_err());
+        assert!(Scene::parse("emitter circle 0 0 -inf").is_err());
+        assert!(Scene::parse("emitter point 0 0\nrate NaN").is_err());
+
+    }
+
+    #[test]
+    fn rejects_radii_too_big_for_the_world() {
+
+        let mut world = PhysicsWorld::new(640, 480);
+
+        assert!(world.add_scene(Scene::parse("emitter point 0 0\nradius 1 241").unwrap()).is_err());
+        assert!(world.get_emitter_ids().is_empty());
+        assert!(world.add_scene(Scene::parse("emitter point 0 0\nradius 1 240").unwrap()).is_ok());
+
+    }
+
+    #[test]
+    fn rejects_colors_out_of_range() {
+
+        assert!(Scene::parse("emitter point 0 0\ncolor 256 0 0").is_err());
+        assert!(Scene::parse("emitter point 0 0\ncolor 0 -1 0").is_err());
+
+    }
+
+}
@@ -0,0 +1,152 @@
+use cgmath::InnerSpace;
+use crate::physics_object::PhysicsWorld;
+
+fn find_root(parents: &mut Vec<usize>, mut index: usize) -> usize {
+
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+
+    return index;
+
+}
+
+impl PhysicsWorld {
+
+    /// Advances every awake object's sleep timer, groups touching objects into islands and then either puts a
+    /// whole island to sleep, once all of it has been slow for long enough, or wakes all of it.
+    ///
+    /// Sleeping objects never report contacts with each other, so waking spreads through a resting pile one layer
+    /// of contacts per update.
+    pub(crate) fn update_sleeping(&mut self, contacts: &[(usize, usize)], d_t: f64) {
+
+        let threshold = self.get_sleep_velocity_threshold();
+        let time_to_sleep = self.get_time_to_sleep();
+        let objects = self.get_objects_mut_no_wake();
+
+        let mut parents: Vec<usize> = (0..objects.len()).collect();
+
+        for &(a, b) in contacts {
+            let root_a = find_root(&mut parents, a);
+            let root_b = find_root(&mut parents, b);
+            parents[root_a] = root_b;
+        }
+
+        // An island stays awake if any member has not yet been slow for `time_to_sleep`.
+        let mut island_awake = vec![false; objects.len()];
+
+        for index in 0..objects.len() {
+
+            let object = &mut objects[index];
+
+            if !object.is_sleeping() {
+
+                if object.get_velocity().magnitude() < threshold * d_t {
+                    object.set_sleep_timer(object.get_sleep_timer() + d_t);
+                } else {
+                    object.set_sleep_timer(0.);
+                }
+
+                if object.get_sleep_timer() < time_to_sleep {
+                    let root = find_root(&mut parents, index);
+                    island_awake[root] = true;
+                }
+
+            }
+
+        }
+
+        for index in 0..objects.len() {
+
+            let root = find_root(&mut parents, index);
+            let object = &mut objects[index];
+
+            if island_awake[root] {
+                if object.is_sleeping() {
+                    object.wake();
+                }
+            } else if !object.is_sleeping() {
+                object.sleep();
+            }
+
+        }
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use cgmath::Vector2;
+    use sfml::graphics::CircleShape;
+    use crate::circle::Circle;
+    use super::*;
+
+    const D_T: f64 = 0.1;
+
+    /// Two touching objects at 0 and 1 and a loner at 2, all at rest.
+    fn world() -> PhysicsWorld {
+
+        let mut world = PhysicsWorld::new(640, 640);
+
+        for x in [100., 104., 300.] {
+            world.push_object(Circle::new(Vector2::new(x, 100.), 2., 1.), CircleShape::new(2., 8));
+        }
+
+        return world;
+
+    }
+
+    fn settle(world: &mut PhysicsWorld, contacts: &[(usize, usize)], moving: Option<usize>) {
+
+        for _ in 0..20 {
+            if let Some(index) = moving {
+                world.get_objects_mut_no_wake()[index].set_velocity(Vector2::new(100. * D_T, 0.));
+            }
+            world.update_sleeping(contacts, D_T);
+        }
+
+    }
+
+    fn sleeping(world: &PhysicsWorld) -> Vec<bool> {
+        world.get_objects().iter().map(|object| object.is_sleeping()).collect()
+    }
+
+    #[test]
+    fn resting_islands_fall_asleep() {
+
+        let mut world = world();
+        settle(&mut world, &[(0, 1)], None);
+
+        assert_eq!(sleeping(&world), vec![true, true, true]);
+
+    }
+
+    #[test]
+    fn one_moving_member_keeps_its_whole_island_awake() {
+
+        let mut world = world();
+        settle(&mut world, &[(0, 1)], Some(0));
+
+        assert_eq!(sleeping(&world), vec![false, false, true]);
+
+    }
+
+    #[test]
+    fn waking_one_member_wakes_its_island() {
+
+        let mut world = world();
+        settle(&mut world, &[(0, 1)], None);
+
+        let object = &mut world.get_objects_mut_no_wake()[0];
+        object.wake();
+        object.set_velocity(Vector2::new(100. * D_T, 0.));
+        world.update_sleeping(&[(0, 1)], D_T);
+
+        assert_eq!(sleeping(&world), vec![false, false, true]);
+
+    }
+
+}
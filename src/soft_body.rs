@@ -0,0 +1,155 @@
+use cgmath::{InnerSpace, Vector2};
+use crate::circle::Circle;
+use crate::physics_object::ObjectHandle;
+use crate::rigid_body::{best_fit_rotation, center_of_mass, rotate};
+
+/// Identifies a soft body added to a [`PhysicsWorld`](crate::physics_object::PhysicsWorld).
+pub type SoftBodyId = usize;
+
+/// A group of circles pulled back towards a rest shape, after Müller et al.'s meshless shape matching.
+///
+/// Each update the rest shape is fitted to the members with the best rotation and each member is moved part of the
+/// way towards its place in it. Unlike a web of [`DistanceConstraint`](crate::constraint::DistanceConstraint)s the
+/// shape can't fold over or collapse, since the fit always comes out the right way round.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoftBody {
+
+    members: Vec<ObjectHandle>,
+    rest_offsets: Vec<Vector2<f64>>,
+    stiffness: f64,
+    plastic_yield: Option<f64>,
+    plastic_creep: f64,
+    angle: f64,
+
+}
+
+impl SoftBody {
+
+    pub(crate) fn new(members: Vec<ObjectHandle>, stiffness: f64, objects: &[Circle], indices: &[usize]) -> SoftBody {
+
+        let mut body = SoftBody {
+            members,
+            rest_offsets: Vec::new(),
+            stiffness,
+            plastic_yield: None,
+            plastic_creep: 0.,
+            angle: 0.,
+        };
+
+        body.capture(objects, indices);
+
+        return body;
+
+    }
+
+    /// Takes the members' current arrangement as the rest shape.
+    pub(crate) fn capture(&mut self, objects: &[Circle], indices: &[usize]) {
+
+        let positions: Vec<Vector2<f64>> = indices.iter().map(|&index| objects[index].get_position()).collect();
+        let masses: Vec<f64> = indices.iter().map(|&index| objects[index].get_mass()).collect();
+        let center = center_of_mass(&positions, &masses);
+
+        self.rest_offsets = positions.iter().map(|&position| rotate(position - center, -self.angle)).collect();
+
+    }
+
+    /// Drops members that are no longer in the world and takes the rest of them as the new rest shape.
+    pub(crate) fn retain_members(&mut self, objects: &[Circle], resolve: impl Fn(ObjectHandle) -> Option<usize>) {
+
+        let before = self.members.len();
+        self.members.retain(|&handle| resolve(handle).is_some());
+
+        if self.members.len() != before && !self.members.is_empty() {
+            let indices: Vec<usize> = self.members.iter().filter_map(|&handle| resolve(handle)).collect();
+            self.capture(objects, &indices);
+        }
+
+    }
+
+    /// Pulls the members given by `indices` (in the same order as [`SoftBody::get_members`]) towards the rest shape.
+    pub(crate) fn solve(&mut self, objects: &mut [Circle], indices: &[usize]) {
+
+        let positions: Vec<Vector2<f64>> = indices.iter().map(|&index| objects[index].get_position()).collect();
+        let masses: Vec<f64> = indices.iter().map(|&index| objects[index].get_mass()).collect();
+
+        let center = center_of_mass(&positions, &masses);
+        self.angle = best_fit_rotation(&positions, &masses, &self.rest_offsets, center, self.angle);
+
+        for (member, &index) in indices.iter().enumerate() {
+
+            // Kinematic members follow their motion; the rest of the body is pulled around them.
+            if objects[index].is_kinematic() {
+                continue;
+            }
+
+            let goal = center + rotate(self.rest_offsets[member], self.angle);
+            objects[index].set_raw_position(positions[member] + (goal - positions[member]) * self.stiffness);
+
+        }
+
+        if let Some(plastic_yield) = self.plastic_yield {
+
+            let mut deformed = false;
+
+            for member in 0..indices.len() {
+
+                let offset = rotate(positions[member] - center, -self.angle);
+                let strain = offset - self.rest_offsets[member];
+
+                if strain.magnitude() > plastic_yield {
+                    self.rest_offsets[member] += strain.normalize() * (strain.magnitude() - plastic_yield) * self.plastic_creep;
+                    deformed = true;
+                }
+
+            }
+
+            // Creep can drag the rest shape off centre, which would read as the body wanting to move.
+            if deformed {
+                let rest_center = center_of_mass(&self.rest_offsets, &masses);
+                for offset in self.rest_offsets.iter_mut() {
+                    *offset -= rest_center;
+                }
+            }
+
+        }
+
+    }
+
+    pub fn get_members(&self) -> &Vec<ObjectHandle> {
+        &self.members
+    }
+
+    /// The fraction of the way each member is moved towards its place in the fitted rest shape each update, from 0 to 1.
+    pub fn get_stiffness(&self) -> f64 {
+        self.stiffness
+    }
+
+    pub fn set_stiffness(&mut self, stiffness: f64) {
+        self.stiffness = stiffness;
+    }
+
+    /// How far a member may be pushed from its place in the rest shape before the rest shape starts to give way.
+    /// `None` makes the body perfectly elastic.
+    pub fn get_plastic_yield(&self) -> Option<f64> {
+        self.plastic_yield
+    }
+
+    pub fn set_plastic_yield(&mut self, plastic_yield: Option<f64>) {
+        self.plastic_yield = plastic_yield;
+    }
+
+    /// The fraction of the deformation past the yield that is absorbed into the rest shape each update, from 0 to 1.
+    pub fn get_plastic_creep(&self) -> f64 {
+        self.plastic_creep
+    }
+
+    pub fn set_plastic_creep(&mut self, plastic_creep: f64) {
+        self.plastic_creep = plastic_creep;
+    }
+
+    /// How far the fitted rest shape has turned from the arrangement the body was created with.
+    pub fn get_angle(&self) -> f64 {
+        self.angle
+    }
+
+}
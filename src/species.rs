@@ -0,0 +1,275 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use cgmath::InnerSpace;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sfml::graphics::Color;
+use crate::physics_object::PhysicsWorld;
+
+/// Fraction of an interaction radius inside which two particles always repel, whatever their species.
+const CORE_FRACTION: f64 = 0.3;
+
+/// How strongly, and from how far, particles of each species are attracted to (or repelled by) every other species.
+///
+/// Interactions need not be symmetric: `get_strength(a, b)` is how a particle of species `a` reacts to one of species `b`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InteractionMatrix {
+
+    species_count: usize,
+    strengths: Vec<f64>,
+    radii: Vec<f64>,
+    repulsion: f64,
+
+}
+
+impl InteractionMatrix {
+
+    /// A matrix where nothing interacts.
+    pub fn new(species_count: usize) -> InteractionMatrix {
+        InteractionMatrix {
+            species_count,
+            strengths: vec![0.; species_count * species_count],
+            radii: vec![0.; species_count * species_count],
+            repulsion: 1.,
+        }
+    }
+
+    /// Picks every strength uniformly from `-max_strength..max_strength` and every radius from `min_radius..max_radius`,
+    /// so the same seed always gives the same matrix. A `max_radius` at or below `min_radius` gives every radius as
+    /// `min_radius`, the same as an [`Emitter`](crate::emitter::Emitter)'s ranges.
+    pub fn random(species_count: usize, seed: u64, max_strength: f64, min_radius: f64, max_radius: f64) -> InteractionMatrix {
+
+        let max_strength = max_strength.abs();
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut matrix = InteractionMatrix::new(species_count);
+
+        for index in 0..species_count * species_count {
+            matrix.strengths[index] = rng.gen_range(-max_strength..=max_strength);
+            matrix.radii[index] = if max_radius > min_radius { rng.gen_range(min_radius..=max_radius) } else { min_radius };
+        }
+
+        matrix.repulsion = max_strength;
+
+        return matrix;
+
+    }
+
+    /// Reads a matrix written by [`InteractionMatrix::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<InteractionMatrix> {
+
+        InteractionMatrix::parse(&fs::read_to_string(path)?)
+
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+
+        fs::write(path, self.to_string())
+
+    }
+
+    /// Parses the text format:
+    ///
+    /// ```text
+    /// species 2
+    /// repulsion 50
+    /// strengths
+    /// 10 -5
+    /// 0 20
+    /// radii
+    /// 30 30
+    /// 30 30
+    /// ```
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> io::Result<InteractionMatrix> {
+
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let mut matrix: Option<InteractionMatrix> = None;
+
+        while let Some(line) = lines.next() {
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or("");
+
+            match keyword {
+                "species" => {
+                    let count = words.next().and_then(|word| word.parse().ok()).ok_or_else(|| invalid(format!("bad species count: {}", line)))?;
+                    matrix = Some(InteractionMatrix::new(count));
+                }
+                "repulsion" => {
+                    let matrix = matrix.as_mut().ok_or_else(|| invalid(String::from("repulsion given before species")))?;
+                    matrix.repulsion = words.next().and_then(|word| word.parse().ok()).ok_or_else(|| invalid(format!("bad repulsion: {}", line)))?;
+                }
+                "strengths" | "radii" => {
+
+                    let matrix = matrix.as_mut().ok_or_else(|| invalid(format!("{} given before species", keyword)))?;
+                    let species_count = matrix.species_count;
+                    let target = if keyword == "strengths" { &mut matrix.strengths } else { &mut matrix.radii };
+
+                    for row in 0..species_count {
+
+                        let row_line = lines.next().ok_or_else(|| invalid(format!("{} ended after {} rows", keyword, row)))?;
+                        let values: Vec<f64> = row_line.split_whitespace().map(|word| word.parse()).collect::<Result<_, _>>()
+                            .map_err(|_| invalid(format!("bad number in {} row: {}", keyword, row_line)))?;
+
+                        if values.len() != species_count {
+                            return Err(invalid(format!("{} row has {} values, expected {}", keyword, values.len(), species_count)));
+                        }
+
+                        target[row * species_count..(row + 1) * species_count].copy_from_slice(&values);
+
+                    }
+
+                }
+                _ => return Err(invalid(format!("unknown line: {}", line))),
+            }
+
+        }
+
+        matrix.ok_or_else(|| invalid(String::from("missing species count")))
+
+    }
+
+    pub fn get_species_count(&self) -> usize {
+        self.species_count
+    }
+
+    pub fn get_strength(&self, species: usize, other_species: usize) -> f64 {
+        self.strengths[species * self.species_count + other_species]
+    }
+
+    pub fn set_strength(&mut self, species: usize, other_species: usize, strength: f64) {
+        self.strengths[species * self.species_count + other_species] = strength;
+    }
+
+    pub fn get_radius(&self, species: usize, other_species: usize) -> f64 {
+        self.radii[species * self.species_count + other_species]
+    }
+
+    pub fn set_radius(&mut self, species: usize, other_species: usize, radius: f64) {
+        self.radii[species * self.species_count + other_species] = radius;
+    }
+
+    /// The acceleration with which every pair is pushed apart when they get too close, whatever their species.
+    pub fn get_repulsion(&self) -> f64 {
+        self.repulsion
+    }
+
+    pub fn set_repulsion(&mut self, repulsion: f64) {
+        self.repulsion = repulsion;
+    }
+
+    pub fn get_max_radius(&self) -> f64 {
+        self.radii.iter().cloned().fold(0., f64::max)
+    }
+
+    /// The acceleration towards the other particle felt by one of `species` at `distance` from one of `other_species`.
+    fn acceleration(&self, species: usize, other_species: usize, distance: f64) -> f64 {
+
+        let radius = self.get_radius(species, other_species);
+        let core = radius * CORE_FRACTION;
+
+        if distance >= radius || radius <= 0. {
+            0.
+        } else if distance < core {
+            self.repulsion * (distance / core - 1.)
+        } else {
+            self.get_strength(species, other_species) * (1. - (2. * distance - radius - core).abs() / (radius - core))
+        }
+
+    }
+
+}
+
+impl std::fmt::Display for InteractionMatrix {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+
+        writeln!(f, "species {}", self.species_count)?;
+        writeln!(f, "repulsion {}", self.repulsion)?;
+
+        for (keyword, values) in [("strengths", &self.strengths), ("radii", &self.radii)] {
+
+            writeln!(f, "{}", keyword)?;
+
+            for row in values.chunks(self.species_count.max(1)) {
+                let words: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+                writeln!(f, "{}", words.join(" "))?;
+            }
+
+        }
+
+        Ok(())
+
+    }
+
+}
+
+/// A distinct colour for each species, for use in a draw predicate.
+pub fn species_color(species: usize) -> Color {
+
+    // Step around the hue wheel by the golden angle so neighbouring ids look different.
+    let hue = (species as f64 * 137.508) % 360.;
+    let sector = hue / 60.;
+    let falling = 1. - (sector % 2. - 1.).abs();
+
+    let (red, green, blue) = match sector as u32 {
+        0 => (1., falling, 0.),
+        1 => (falling, 1., 0.),
+        2 => (0., 1., falling),
+        3 => (0., falling, 1.),
+        4 => (falling, 0., 1.),
+        _ => (1., 0., falling),
+    };
+
+    Color::rgb((red * 255.) as u8, (green * 255.) as u8, (blue * 255.) as u8)
+
+}
+
+impl PhysicsWorld {
+
+    /// Applies the interaction matrix between every pair of particles within its largest radius.
+    pub(crate) fn apply_species_interactions(&mut self) {
+
+        let matrix = match self.get_interaction_matrix() {
+            Some(matrix) => matrix.clone(),
+            None => return,
+        };
+
+        let pairs = self.query_index_pairs(matrix.get_max_radius());
+        let objects = self.get_objects_mut_no_wake();
+
+        for (a, b) in pairs {
+
+            let species_a = objects[a].get_species();
+            let species_b = objects[b].get_species();
+
+            if species_a >= matrix.get_species_count() || species_b >= matrix.get_species_count() {
+                continue;
+            }
+
+            let offset = objects[b].get_position() - objects[a].get_position();
+            let distance = offset.magnitude();
+
+            if distance == 0. {
+                continue;
+            }
+
+            let direction = offset / distance;
+            let acceleration_a = matrix.acceleration(species_a, species_b, distance);
+            let acceleration_b = matrix.acceleration(species_b, species_a, distance);
+
+            let mass_a = objects[a].get_mass();
+            let mass_b = objects[b].get_mass();
+            objects[a].force(direction * (acceleration_a * mass_a));
+            objects[b].force(-direction * (acceleration_b * mass_b));
+
+        }
+
+    }
+
+}
@@ -0,0 +1,196 @@
+use cgmath::Vector2;
+use sfml::graphics::Color;
+use crate::circle::Circle;
+use crate::physics_object::{ObjectHandle, PhysicsWorld};
+use crate::query::Aabb;
+
+/// The temperature at which objects have their rest size.
+pub const REFERENCE_TEMPERATURE: f64 = 20.;
+
+/// Identifies a heat source added to a [`PhysicsWorld`].
+pub type HeatSourceId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HeatRegion {
+    Aabb(Aabb),
+    Circle { center: Vector2<f64>, radius: f64 },
+}
+
+/// A heater or cooler that pulls the temperature of every object inside its region towards its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeatSource {
+
+    region: HeatRegion,
+    temperature: f64,
+    rate: f64,
+
+}
+
+impl HeatSource {
+
+    /// `rate` is how quickly objects approach `temperature`: the fraction of the difference closed per second,
+    /// for small time steps.
+    pub fn new(region: HeatRegion, temperature: f64, rate: f64) -> HeatSource {
+        HeatSource {
+            region,
+            temperature,
+            rate,
+        }
+    }
+
+    pub fn get_region(&self) -> HeatRegion {
+        self.region
+    }
+
+    pub fn set_region(&mut self, region: HeatRegion) {
+        self.region = region;
+    }
+
+    pub fn get_temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    pub fn set_temperature(&mut self, temperature: f64) {
+        self.temperature = temperature;
+    }
+
+    pub fn get_rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+    }
+
+    fn affected_objects(&self, world: &PhysicsWorld) -> Vec<ObjectHandle> {
+
+        match self.region {
+            HeatRegion::Aabb(aabb) => world.query_aabb(aabb),
+            HeatRegion::Circle { center, radius } => world.query_circle(center, radius),
+        }
+
+    }
+
+}
+
+/// Maps a temperature onto a black, red, yellow, white ramp between `min` and `max`, for use in a draw predicate.
+pub fn temperature_color(temperature: f64, min: f64, max: f64) -> Color {
+
+    let heat = if max > min { ((temperature - min) / (max - min)).clamp(0., 1.) } else { 0. };
+
+    let red = (heat * 3.).min(1.);
+    let green = (heat * 3. - 1.).clamp(0., 1.);
+    let blue = (heat * 3. - 2.).clamp(0., 1.);
+
+    Color::rgb((red * 255.) as u8, (green * 255.) as u8, (blue * 255.) as u8)
+
+}
+
+/// Moves heat from the hotter object to the colder one, never so much that they swap which is hotter.
+///
+/// `other_capacity` of `None` stands for something that holds its temperature, like a heated wall. Nothing is
+/// exchanged with an object that can't hold heat.
+fn exchange_heat(object: &mut Circle, other_temperature: f64, other_capacity: Option<f64>, conductance: f64, d_t: f64) -> f64 {
+
+    let capacity = object.get_mass() * object.get_material().get_heat_capacity();
+
+    if capacity <= 0. || other_capacity.map_or(false, |other_capacity| other_capacity <= 0.) {
+        return 0.;
+    }
+
+    let difference = other_temperature - object.get_temperature();
+
+    let combined_capacity = match other_capacity {
+        Some(other_capacity) => capacity * other_capacity / (capacity + other_capacity),
+        None => capacity,
+    };
+
+    let heat = conductance * difference * d_t;
+    let heat = if difference > 0. { heat.min(difference * combined_capacity) } else { heat.max(difference * combined_capacity) };
+
+    object.set_temperature(object.get_temperature() + heat / capacity);
+
+    return heat;
+
+}
+
+/// Conducts heat between a wall with a fixed temperature and an object touching it.
+pub(crate) fn conduct_heat_from_wall(object: &mut Circle, wall_temperature: f64, d_t: f64) {
+
+    let conductance = object.get_material().get_conductivity();
+    exchange_heat(object, wall_temperature, None, conductance, d_t);
+
+}
+
+impl PhysicsWorld {
+
+    /// Conducts heat across every contact, with a conductance that is the harmonic mean of the two materials'.
+    pub(crate) fn conduct_heat(&mut self, contacts: &[(usize, usize)], d_t: f64) {
+
+        let objects = self.get_objects_mut_no_wake();
+
+        for &(a, b) in contacts {
+
+            let conductivity_a = objects[a].get_material().get_conductivity();
+            let conductivity_b = objects[b].get_material().get_conductivity();
+
+            if conductivity_a + conductivity_b <= 0. {
+                continue;
+            }
+
+            let conductance = 2. * conductivity_a * conductivity_b / (conductivity_a + conductivity_b);
+            let capacity_b = objects[b].get_mass() * objects[b].get_material().get_heat_capacity();
+            let temperature_b = objects[b].get_temperature();
+
+            let heat = exchange_heat(&mut objects[a], temperature_b, Some(capacity_b), conductance, d_t);
+
+            if heat != 0. {
+                objects[b].set_temperature(temperature_b - heat / capacity_b);
+            }
+
+        }
+
+    }
+
+    pub(crate) fn apply_heat_sources(&mut self, d_t: f64) {
+
+        let sources: Vec<HeatSource> = self.get_heat_sources().iter().map(|(_, source)| source.clone()).collect();
+
+        for source in sources {
+
+            let indices: Vec<usize> = source.affected_objects(self).into_iter().filter_map(|handle| self.get_index(handle)).collect();
+            let fraction = 1. - (-source.rate * d_t).exp();
+            let objects = self.get_objects_mut_no_wake();
+
+            for index in indices {
+                let temperature = objects[index].get_temperature();
+                objects[index].set_temperature(temperature + (source.temperature - temperature) * fraction);
+            }
+
+        }
+
+    }
+
+    /// Resizes every object for its temperature and wakes any whose size changed noticeably.
+    pub(crate) fn apply_thermal_expansion(&mut self) {
+
+        let mut max_object_size: f64 = 0.;
+
+        for object in self.get_objects_mut_no_wake().iter_mut() {
+
+            let old_size = object.get_size();
+            object.apply_thermal_expansion();
+
+            if (object.get_size() - old_size).abs() > old_size * 1e-3 {
+                object.wake();
+            }
+
+            max_object_size = max_object_size.max(object.get_size());
+
+        }
+
+        self.grow_max_object_size(max_object_size);
+
+    }
+
+}